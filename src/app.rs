@@ -2,14 +2,52 @@ use std::{
     collections::{HashMap, HashSet},
     fs::File,
     io::Write,
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
+use analysis::{ExposureRow, FileDiffSummary, TagFrequency, TagFrequencySort};
+use command::Action;
+use config::{Config, DirConfig, DisplayPrefs, FileOrder};
 use copypasta::ClipboardContext;
 use directories::UserDirs;
-use et_wrapper::{ExiftoolEntry, TagEntry};
+use et_wrapper::{EtJob, EtVal, ExiftoolEntry, TagEntry, TagEntryKey};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use storage::{Bookmark, Session, Snapshot, Storage};
+use tempfile::TempDir;
+use walkdir::WalkDir;
 
+pub mod analysis;
+pub mod archive;
+pub mod command;
+pub mod config;
 pub mod et_wrapper;
+pub mod export;
+pub mod gps;
+pub mod hexview;
+pub mod history;
+pub mod keywords;
+pub mod lens;
+pub mod storage;
+
+/// How many leading bytes of binary tags are kept for details preview
+pub const BINARY_PREVIEW_LEN: usize = 64;
+/// Shown instead of binary actions for metadata-only files
+pub const SIDECAR_BINARY_MSG: &str =
+    "Sidecar files hold metadata only, binary data is not extracted from them";
+pub const SNAPSHOT_BINARY_MSG: &str =
+    "Snapshots hold metadata only, binary data of the file is not kept in them";
+
+/// Horizontal scroll presses closer than this are treated as a held key
+const HSCROLL_REPEAT_WINDOW: Duration = Duration::from_millis(150);
+const HSCROLL_MAX_STEP: i8 = 16;
+
+/// Tags changed by a reload stay highlighted this long
+const CHANGE_HIGHLIGHT: Duration = Duration::from_secs(10);
+/// How many previous values of a tag are remembered across reloads
+const VALUE_HISTORY_LEN: usize = 5;
 
 #[derive(Default)]
 pub enum MainInput {
@@ -17,6 +55,24 @@ pub enum MainInput {
     Main,
     Filter,
     BinarySaveDialog,
+    HexView,
+    Command,
+    Search,
+    EditValue,
+    Bookmarks,
+    SessionSave,
+    Snapshots,
+    SnapshotSave,
+    Extractions,
+    BatchSummary,
+    GeotagTrack,
+    TimeShift,
+    ExpectedValue,
+    WriteConfirm,
+    CopyTagsPicker,
+    ComparePicker,
+    CommandTemplates,
+    ViewName,
 }
 
 pub struct BinarySaveDialog {
@@ -26,158 +82,2384 @@ pub struct BinarySaveDialog {
     pub editing_fname: bool,
 }
 
-impl Default for BinarySaveDialog {
-    fn default() -> Self {
-        Self {
-            fname: String::new(),
-            fext: String::from("jpeg"),
-            status: Ok(String::from(
-                "File will be saved in Downloads. You probably want a .jpeg.",
-            )),
-            editing_fname: true,
+impl BinarySaveDialog {
+    /// Preselects extension if type of binary data is known
+    pub fn new(detected_type: Option<(&str, &str)>) -> Self {
+        match detected_type {
+            Some((name, ext)) => Self {
+                fext: ext.to_owned(),
+                status: Ok(format!(
+                    "File will be saved in Downloads. Detected type: {name}."
+                )),
+                ..Default::default()
+            },
+            None => Default::default(),
+        }
+    }
+}
+
+impl Default for BinarySaveDialog {
+    fn default() -> Self {
+        Self {
+            fname: String::new(),
+            fext: String::from("jpeg"),
+            status: Ok(String::from(
+                "File will be saved in Downloads. You probably want a .jpeg.",
+            )),
+            editing_fname: true,
+        }
+    }
+}
+
+/// Named filter which remembers its own cursor
+pub struct View {
+    pub name: String,
+    pub filter: String,
+    pub cursor: usize,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareMode {
+    #[default]
+    Off,
+    All,
+    DiffOnly,
+    MatchesOnly,
+}
+
+impl CompareMode {
+    pub fn is_on(&self) -> bool {
+        *self != CompareMode::Off
+    }
+
+    pub fn is_off(&self) -> bool {
+        *self == CompareMode::Off
+    }
+
+    /// Cycles row filtering while compare view is on
+    pub fn next_filter(self) -> Self {
+        match self {
+            CompareMode::Off => CompareMode::Off,
+            CompareMode::All => CompareMode::DiffOnly,
+            CompareMode::DiffOnly => CompareMode::MatchesOnly,
+            CompareMode::MatchesOnly => CompareMode::All,
+        }
+    }
+}
+
+/// Order of tags in tag list and compare view, `None` keeps the order exiftool printed them in
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SortMode {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "name")]
+    NameAsc,
+    /// Tags without ID go last
+    #[serde(rename = "id")]
+    IdAsc,
+    #[serde(rename = "group")]
+    GroupThenName,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::None => SortMode::NameAsc,
+            SortMode::NameAsc => SortMode::IdAsc,
+            SortMode::IdAsc => SortMode::GroupThenName,
+            SortMode::GroupThenName => SortMode::None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::None => "none",
+            SortMode::NameAsc => "name",
+            SortMode::IdAsc => "id",
+            SortMode::GroupThenName => "group",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            SortMode::None,
+            SortMode::NameAsc,
+            SortMode::IdAsc,
+            SortMode::GroupThenName,
+        ]
+        .into_iter()
+        .find(|mode| mode.label() == label.to_lowercase())
+    }
+
+    /// Key tags are ordered by, meant for `sort_by_cached_key` so that names are lowercased once per tag.
+    /// Names are compared as shown, short or detailed, ignoring case
+    pub fn sort_key(self, e: &TagEntry, short: bool) -> (bool, Option<u64>, String, String) {
+        let name = if short {
+            e.short_name.to_lowercase()
+        } else {
+            e.name.to_lowercase()
+        };
+        match self {
+            SortMode::None | SortMode::NameAsc => (false, None, String::new(), name),
+            SortMode::IdAsc => (e.id.is_none(), e.id, String::new(), name),
+            SortMode::GroupThenName => (false, None, e.table_to_string(), name),
+        }
+    }
+}
+
+/// Action modifying files, which waits for user's confirmation before running
+pub enum WriteAction {
+    Geotag(PathBuf),
+    /// Holds ready exiftool argument, e.g. `-AllDates+=1:00:00`
+    TimeShift(String),
+    Strip,
+    /// Indices of loaded files
+    CopyTags {
+        source: usize,
+        targets: Vec<usize>,
+    },
+    /// Empty value removes the tag
+    SetTag {
+        tag: String,
+        value: String,
+    },
+}
+
+/// New value of a tag being typed, written to current file once confirmed
+pub struct ValueEditDialog {
+    /// Tag qualified with its group, e.g. `XMP:Title`
+    pub tag: String,
+    pub value: String,
+    /// Value tag had when dialog was opened
+    pub original: String,
+}
+
+/// Binary data saved in this session, kept to find the output or extract it again
+pub struct Extraction {
+    pub source: PathBuf,
+    pub entry: TagEntry,
+    pub path: PathBuf,
+}
+
+/// How a write went for one file
+#[derive(Debug, PartialEq, Eq)]
+pub enum WriteOutcome {
+    Updated,
+    /// Exiftool ran fine, but had nothing to change
+    Unchanged,
+    Failed,
+}
+
+/// Result of a write for one file, kept until batch summary is dismissed
+pub struct BatchResult {
+    pub file: PathBuf,
+    pub outcome: WriteOutcome,
+    /// Exiftool output
+    pub message: String,
+}
+
+impl BatchResult {
+    fn new(file: PathBuf, res: Result<String, String>) -> Self {
+        let (outcome, message) = match res {
+            Err(e) => (WriteOutcome::Failed, e),
+            // Unchanged files are reported as `0 image files updated, 1 image files unchanged`
+            Ok(out) if out.split(", ").any(|line| line == "0 image files updated") => {
+                (WriteOutcome::Unchanged, out)
+            }
+            Ok(out) => (WriteOutcome::Updated, out),
+        };
+        Self {
+            file,
+            outcome,
+            message,
+        }
+    }
+}
+
+/// Leading bytes of a binary tag being extracted in background
+struct PreviewJob {
+    cache_key: (PathBuf, TagEntryKey),
+    handle: JoinHandle<Vec<u8>>,
+}
+
+/// Exiftool runs reading multiple files in background, in input order
+pub struct Loading {
+    jobs: Vec<EtJob>,
+    pub num_paths: usize,
+    pub started: Instant,
+}
+
+/// Part of main screen which takes scroll keys
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    #[default]
+    List,
+    Details,
+}
+
+/// Tab label of a loaded file, kept until files are loaded, reloaded or closed
+pub struct FileLabel {
+    /// Shortest trailing part of path that no other loaded path ends with,
+    /// e.g. `2024/IMG_001.jpg` and `2025/IMG_001.jpg` instead of two `IMG_001.jpg`
+    pub path: String,
+    /// Completeness score, as `(present, scored)`
+    pub completeness: (usize, usize),
+}
+
+#[derive(Default)]
+pub struct CompareData {
+    pub mode: CompareMode,
+    /// Loaded files taking part in compare, values of each row go in this order
+    pub files: Vec<usize>,
+    pub data: Vec<(TagEntry, Vec<Option<TagEntry>>)>,
+    /// Values typed by user which files are checked against instead of each other
+    pub expected: HashMap<TagEntryKey, String>,
+}
+
+pub struct MainState {
+    pub current_file: PathBuf,
+    pub show_details: bool,
+    pub focus: Pane,
+    pub details_scroll: u16,
+    /// Set while drawing, details pane can't scroll past it
+    pub details_scroll_max: u16,
+    pub binary_save_dialog: Option<BinarySaveDialog>,
+    pub value_edit_dialog: Option<ValueEditDialog>,
+    pub hex_view: Option<hexview::HexView>,
+    pub filter: String,
+    pub sort_mode: SortMode,
+    pub command_input: String,
+    /// Text searched with `/`, matching tags stay among the others unlike with filter
+    pub search_query: String,
+    /// Cursor position search started from, restored if it's cancelled
+    search_origin: usize,
+    /// Candidates of the last Tab completion and which one is in `command_input`
+    command_completion: Option<(Vec<String>, usize)>,
+    pub session_name: String,
+    pub snapshot_name: String,
+    /// Stored snapshots listed in snapshots popup
+    pub snapshot_names: Vec<String>,
+    pub snapshots_cursor: usize,
+    /// Latest first
+    pub extractions: Vec<Extraction>,
+    pub extractions_cursor: usize,
+    /// Tags picked by user, exports and copies take only these while there are any
+    pub marked: HashSet<TagEntryKey>,
+    /// Per file results of the last write touching several files, shown until dismissed
+    pub batch_results: Vec<BatchResult>,
+    pub batch_scroll: usize,
+    pub view_name: String,
+    pub open_path_input: String,
+    pub recents_cursor: usize,
+    pub geotag_input: String,
+    pub time_shift_input: String,
+    pub expected_input: String,
+    /// Write actions apply to all loaded files instead of only the current one
+    pub write_to_all: bool,
+    /// Loaded file tags are copied from in copy tags picker
+    pub copy_tags_source: usize,
+    /// Which loaded files are picked to receive copied tags
+    pub copy_tags_targets: Vec<bool>,
+    pub copy_tags_cursor: usize,
+    /// Which loaded files are picked for compare in compare picker, applied on confirm
+    pub compare_picks: Vec<bool>,
+    pub compare_picks_cursor: usize,
+    pub pending_write: Option<WriteAction>,
+    /// Writes pass `-overwrite_original`, so exiftool doesn't keep `_original` backups
+    pub overwrite_original: bool,
+    /// Temp folders with extracted zip archives, removed on drop
+    extracted_archives: Vec<TempDir>,
+    pub views: Vec<View>,
+    pub active_view: Option<usize>,
+    pub num_entries_shown: usize,
+    /// Rows each shown entry took in the last drawn tag list, more than one for vertical arrays
+    pub entry_rows_shown: Vec<usize>,
+    /// Rows fitting in the last drawn tag list, a page for `<PgUp/PgDn>`
+    pub rows_in_viewport: usize,
+    /// Tag list area of the last drawn frame, for hit-testing mouse position
+    pub main_area: Rect,
+    /// Shown entry under mouse pointer
+    pub hovered: Option<usize>,
+    /// Window of file columns shown in compare mode, set while drawing
+    pub compare_first_column: usize,
+    pub compare_columns_shown: usize,
+    /// Selected array element, as `(cursor, element)` so that it resets when cursor moves
+    array_cursor: (usize, usize),
+    pub et_data: Vec<ExiftoolEntry>,
+    /// Labels of `et_data` files, in the same order
+    pub file_labels: Vec<FileLabel>,
+    pub current_file_index: usize,
+    pub data_display_mode: DataDisplayMode,
+    pub scroll_offset: (u16, u16),
+    pub cursor: usize,
+    user_dirs: UserDirs,
+    pub log_msg: Option<Result<String, String>>,
+    pub config: Config,
+    /// Display toggles as they were on launch, only changed ones are saved on quit
+    loaded_display_prefs: DisplayPrefs,
+    multiple_files_input: Option<Vec<PathBuf>>,
+    /// Input folders with subfolders, user is still to decide if they are read recursively
+    pub recursion_prompts: Vec<PathBuf>,
+    pub loading: Option<Loading>,
+    /// Files in the first prompted folder, read without and with recursion
+    pub recursion_file_counts: (usize, usize),
+    recursive_dirs: Vec<PathBuf>,
+    pub compare_data: CompareData,
+    pub storage: Storage,
+    pub bookmarks_cursor: usize,
+    last_hscroll: Option<(Instant, i8)>,
+    hscroll_step: i8,
+    binary_previews: HashMap<(PathBuf, TagEntryKey), Vec<u8>>,
+    /// Preview for details pane being extracted in background
+    preview_job: Option<PreviewJob>,
+    /// Geolocation tags looked up for GPS summary, by file
+    geolocations: HashMap<PathBuf, Vec<TagEntry>>,
+    pub tag_report: Vec<TagFrequency>,
+    pub tag_report_sort: TagFrequencySort,
+    pub tag_report_cursor: usize,
+    pub diff_summary: Vec<FileDiffSummary>,
+    pub diff_summary_cursor: usize,
+    pub exposure_rows: Vec<ExposureRow>,
+    pub exposure_cursor: usize,
+    pub keyword_rows: Vec<keywords::KeywordRow>,
+    pub keywords_cursor: usize,
+    pub gps: gps::GpsSummary,
+    pub gps_cursor: usize,
+    /// Show only tags from `config.essential_tags`
+    pub essentials_only: bool,
+    /// Resolved from `config.theme` on startup
+    pub light_theme: bool,
+    /// Executable every exiftool run uses, given with `--exiftool-path`
+    pub exiftool: PathBuf,
+    /// Forced compact layout on/off, `None` means it depends on terminal width
+    pub compact_mode: Option<bool>,
+    /// File other files are diffed against, with its tags indexed by key
+    reference: Option<(usize, HashMap<TagEntryKey, TagEntry>)>,
+    pub reference_diff_only: bool,
+    /// Previous values of tags which changed on reloads, oldest first
+    value_history: HashMap<(PathBuf, TagEntryKey), Vec<String>>,
+    /// Tags changed by the last reload and when it happened
+    last_reload_changes: Option<(Instant, HashSet<(PathBuf, TagEntryKey)>)>,
+}
+
+/// Counts files in a folder without reading them, to estimate how long exiftool will take
+fn count_files(dir: &Path, recursive: bool) -> usize {
+    WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .count()
+}
+
+/// Drops files read more than once, e.g. listed twice or reached through a symlink,
+/// first one is kept. Returns how many were dropped
+fn dedup_files(et_data: &mut Vec<ExiftoolEntry>) -> usize {
+    let num_files = et_data.len();
+    let mut seen = HashSet::new();
+    et_data.retain(|et| {
+        seen.insert(std::fs::canonicalize(&et.file_name).unwrap_or(et.file_name.clone()))
+    });
+    num_files - et_data.len()
+}
+
+/// Sorts loaded files by name or by value of `date_tag`, exiftool's `YYYY:MM:DD hh:mm:ss` dates
+/// sort right as text. Sort is stable, so files that compare equal keep input order
+fn sort_files(et_data: &mut [ExiftoolEntry], order: FileOrder, date_tag: &str) {
+    match order {
+        FileOrder::Input => {}
+        FileOrder::Name => et_data.sort_by_cached_key(|et| {
+            et.file_name
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_lowercase()
+        }),
+        FileOrder::Date => et_data.sort_by_cached_key(|et| {
+            let date = et
+                .tag_entries
+                .iter()
+                .find(|e| e.short_name == date_tag)
+                .map(|e| e.val.to_string());
+            (date.is_none(), date)
+        }),
+    }
+}
+
+/// Falls back to default config, reporting the problem through log message
+fn load_config() -> (Config, Option<Result<String, String>>) {
+    match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(Err(e))),
+    }
+}
+
+fn file_labels(et_data: &[ExiftoolEntry], completeness_tags: &[String]) -> Vec<FileLabel> {
+    let paths = et_data
+        .iter()
+        .map(|et| et.file_name.as_path())
+        .collect::<Vec<_>>();
+    path_suffixes(&paths)
+        .into_iter()
+        .zip(et_data)
+        .map(|(path, et)| FileLabel {
+            path,
+            completeness: analysis::completeness(&et.tag_entries, completeness_tags),
+        })
+        .collect()
+}
+
+fn path_suffixes(paths: &[&Path]) -> Vec<String> {
+    let components = paths
+        .iter()
+        .map(|p| p.components().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let suffix = |comps: &[Component], depth: usize| {
+        comps[comps.len().saturating_sub(depth)..]
+            .iter()
+            .collect::<PathBuf>()
+    };
+    components
+        .iter()
+        .enumerate()
+        .map(|(i, comps)| {
+            let depth = (1..comps.len())
+                .find(|&depth| {
+                    let own = suffix(comps, depth);
+                    components
+                        .iter()
+                        .enumerate()
+                        .all(|(j, other)| i == j || suffix(other, depth) != own)
+                })
+                .unwrap_or(comps.len());
+            suffix(comps, depth).to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+/// File managers and terminals wrap dropped paths in quotes or pass them as `file://` urls
+pub fn clean_dropped_path(text: &str) -> String {
+    let text = text.trim();
+    let text = ['"', '\'']
+        .iter()
+        .find_map(|q| text.strip_prefix(*q).and_then(|t| t.strip_suffix(*q)))
+        .unwrap_or(text);
+    text.strip_prefix("file://").unwrap_or(text).to_owned()
+}
+
+impl MainState {
+    fn new(image_path: PathBuf, exiftool: PathBuf) -> std::io::Result<Self> {
+        let (config, log_msg) = load_config();
+        let et_data = et_wrapper::run(
+            &exiftool,
+            vec![image_path.clone()],
+            false,
+            config.structured_xmp,
+        )?;
+        if et_data.is_empty() {
+            return Err(std::io::Error::other("exiftool returned no files"));
+        }
+        let num_entries_shown = et_data[0].tag_entries.len();
+
+        Ok(Self {
+            current_file: image_path,
+            show_details: false,
+            focus: Pane::List,
+            details_scroll: 0,
+            details_scroll_max: 0,
+            binary_save_dialog: None,
+            value_edit_dialog: None,
+            hex_view: None,
+            filter: String::new(),
+            sort_mode: SortMode::None,
+            command_input: String::new(),
+            search_query: String::new(),
+            search_origin: 0,
+            command_completion: None,
+            session_name: String::new(),
+            snapshot_name: String::new(),
+            snapshot_names: Vec::new(),
+            snapshots_cursor: 0,
+            extractions: Vec::new(),
+            extractions_cursor: 0,
+            marked: HashSet::new(),
+            batch_results: Vec::new(),
+            batch_scroll: 0,
+            view_name: String::new(),
+            open_path_input: String::new(),
+            recents_cursor: 0,
+            geotag_input: String::new(),
+            time_shift_input: String::new(),
+            expected_input: String::new(),
+            write_to_all: false,
+            copy_tags_source: 0,
+            copy_tags_targets: Vec::new(),
+            copy_tags_cursor: 0,
+            compare_picks: Vec::new(),
+            compare_picks_cursor: 0,
+            pending_write: None,
+            overwrite_original: false,
+            extracted_archives: Vec::new(),
+            views: Vec::new(),
+            active_view: None,
+            num_entries_shown,
+            entry_rows_shown: Vec::new(),
+            rows_in_viewport: 0,
+            main_area: Rect::default(),
+            hovered: None,
+            compare_first_column: 0,
+            compare_columns_shown: 0,
+            array_cursor: (0, 0),
+            file_labels: file_labels(&et_data, &config.completeness_tags),
+            et_data,
+            current_file_index: 0,
+            data_display_mode: DataDisplayMode {
+                short: config.short_names,
+                numerical: config.numerical_values,
+            },
+            compact_mode: config.compact_layout,
+            scroll_offset: (0, 0),
+            cursor: 0,
+            user_dirs: UserDirs::new().expect("Failed to locate user home dir!"),
+            log_msg,
+            loaded_display_prefs: config.display_prefs(),
+            config,
+            multiple_files_input: None,
+            recursion_prompts: Vec::new(),
+            loading: None,
+            recursion_file_counts: (0, 0),
+            recursive_dirs: Vec::new(),
+            compare_data: Default::default(),
+            storage: Storage::load(),
+            bookmarks_cursor: 0,
+            last_hscroll: None,
+            hscroll_step: 1,
+            binary_previews: HashMap::new(),
+            preview_job: None,
+            geolocations: HashMap::new(),
+            tag_report: Vec::new(),
+            tag_report_sort: Default::default(),
+            tag_report_cursor: 0,
+            diff_summary: Vec::new(),
+            diff_summary_cursor: 0,
+            exposure_rows: Vec::new(),
+            exposure_cursor: 0,
+            keyword_rows: Vec::new(),
+            keywords_cursor: 0,
+            gps: Default::default(),
+            gps_cursor: 0,
+            essentials_only: false,
+            light_theme: false,
+            exiftool,
+            reference: None,
+            reference_diff_only: false,
+            value_history: HashMap::new(),
+            last_reload_changes: None,
+        })
+    }
+
+    fn new_multiple_files(input: Vec<PathBuf>, exiftool: PathBuf) -> Self {
+        let (config, log_msg) = load_config();
+        // First opened folder having its own config decides the initial view
+        let dir_config = input
+            .iter()
+            .filter(|p| p.is_dir())
+            .find_map(|dir| DirConfig::load(dir))
+            .unwrap_or_default();
+        Self {
+            current_file: PathBuf::new(),
+            show_details: false,
+            focus: Pane::List,
+            details_scroll: 0,
+            details_scroll_max: 0,
+            binary_save_dialog: None,
+            value_edit_dialog: None,
+            hex_view: None,
+            filter: dir_config.filter.unwrap_or_default(),
+            sort_mode: dir_config.sort.unwrap_or_default(),
+            command_input: String::new(),
+            search_query: String::new(),
+            search_origin: 0,
+            command_completion: None,
+            session_name: String::new(),
+            snapshot_name: String::new(),
+            snapshot_names: Vec::new(),
+            snapshots_cursor: 0,
+            extractions: Vec::new(),
+            extractions_cursor: 0,
+            marked: HashSet::new(),
+            batch_results: Vec::new(),
+            batch_scroll: 0,
+            view_name: String::new(),
+            open_path_input: String::new(),
+            recents_cursor: 0,
+            geotag_input: String::new(),
+            time_shift_input: String::new(),
+            expected_input: String::new(),
+            write_to_all: false,
+            copy_tags_source: 0,
+            copy_tags_targets: Vec::new(),
+            copy_tags_cursor: 0,
+            compare_picks: Vec::new(),
+            compare_picks_cursor: 0,
+            pending_write: None,
+            overwrite_original: false,
+            extracted_archives: Vec::new(),
+            views: Vec::new(),
+            active_view: None,
+            num_entries_shown: 0,
+            entry_rows_shown: Vec::new(),
+            rows_in_viewport: 0,
+            main_area: Rect::default(),
+            hovered: None,
+            compare_first_column: 0,
+            compare_columns_shown: 0,
+            array_cursor: (0, 0),
+            et_data: Vec::new(),
+            file_labels: Vec::new(),
+            current_file_index: 0,
+            data_display_mode: DataDisplayMode {
+                short: config.short_names,
+                numerical: config.numerical_values,
+            },
+            compact_mode: config.compact_layout,
+            scroll_offset: (0, 0),
+            cursor: 0,
+            user_dirs: UserDirs::new().expect("Failed to locate user home dir!"),
+            log_msg,
+            loaded_display_prefs: config.display_prefs(),
+            config,
+            multiple_files_input: Some(input),
+            recursion_prompts: Vec::new(),
+            loading: None,
+            recursion_file_counts: (0, 0),
+            recursive_dirs: Vec::new(),
+            compare_data: Default::default(),
+            storage: Storage::load(),
+            bookmarks_cursor: 0,
+            last_hscroll: None,
+            hscroll_step: 1,
+            binary_previews: HashMap::new(),
+            preview_job: None,
+            geolocations: HashMap::new(),
+            tag_report: Vec::new(),
+            tag_report_sort: Default::default(),
+            tag_report_cursor: 0,
+            diff_summary: Vec::new(),
+            diff_summary_cursor: 0,
+            exposure_rows: Vec::new(),
+            exposure_cursor: 0,
+            keyword_rows: Vec::new(),
+            keywords_cursor: 0,
+            gps: Default::default(),
+            gps_cursor: 0,
+            essentials_only: false,
+            light_theme: false,
+            exiftool,
+            reference: None,
+            reference_diff_only: false,
+            value_history: HashMap::new(),
+            last_reload_changes: None,
+        }
+    }
+
+    /// Decides recursion for the first prompted folder, or for all of the remaining ones.
+    /// Returns whether every folder is decided on
+    pub fn decide_recursion(&mut self, recursive: bool, for_all: bool) -> bool {
+        let num = if for_all {
+            self.recursion_prompts.len()
+        } else {
+            1.min(self.recursion_prompts.len())
+        };
+        let decided = self.recursion_prompts.drain(..num).collect::<Vec<_>>();
+        if recursive {
+            self.recursive_dirs.extend(decided);
+        }
+        self.count_prompted_files();
+        self.recursion_prompts.is_empty()
+    }
+
+    pub fn count_prompted_files(&mut self) {
+        self.recursion_file_counts = match self.recursion_prompts.first() {
+            Some(dir) => (count_files(dir, false), count_files(dir, true)),
+            None => (0, 0),
+        };
+    }
+
+    /// Starts reading input in background, consecutive paths with the same recursion go to one exiftool run
+    pub fn start_loading(&mut self) -> std::io::Result<()> {
+        let input_files = self.multiple_files_input.take().unwrap();
+        let mut groups: Vec<(bool, Vec<PathBuf>)> = Vec::new();
+        for path in input_files {
+            let recursive = self.recursive_dirs.contains(&path);
+            match groups.last_mut() {
+                Some((r, paths)) if *r == recursive => paths.push(path),
+                _ => groups.push((recursive, vec![path])),
+            }
+        }
+        let num_paths = groups.iter().map(|(_, paths)| paths.len()).sum();
+        let mut jobs = Vec::new();
+        for (recursive, paths) in groups {
+            match EtJob::spawn(&self.exiftool, paths, recursive, self.config.structured_xmp) {
+                Ok(job) => jobs.push(job),
+                Err(e) => {
+                    jobs.into_iter().for_each(EtJob::kill);
+                    return Err(e);
+                }
+            }
+        }
+        self.loading = Some(Loading {
+            jobs,
+            num_paths,
+            started: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Takes loaded data once every exiftool run has exited, None while some are still running
+    pub fn poll_loading(&mut self) -> Option<std::io::Result<()>> {
+        let loading = self.loading.as_mut()?;
+        if !loading.jobs.iter_mut().all(EtJob::is_done) {
+            return None;
+        }
+        Some(self.finish_loading())
+    }
+
+    fn finish_loading(&mut self) -> std::io::Result<()> {
+        let loading = self.loading.take().expect("Loading should be in progress");
+        self.et_data = Vec::new();
+        for job in loading.jobs {
+            self.et_data.extend(job.finish()?);
+        }
+        if self.et_data.is_empty() {
+            return Err(std::io::Error::other("exiftool read no files"));
+        }
+        let duplicates = dedup_files(&mut self.et_data);
+        sort_files(
+            &mut self.et_data,
+            self.config.file_order,
+            &self.config.file_order_date_tag,
+        );
+        if duplicates > 0 {
+            self.log_msg = Some(Ok(format!(
+                "Skipped {duplicates} file(s) read more than once"
+            )));
+        }
+        self.num_entries_shown = self.et_data[0].tag_entries.len();
+        self.current_file = self.et_data[0].file_name.clone();
+        self.refresh_file_labels();
+        self.calculate_compare_data();
+        Ok(())
+    }
+
+    /// Kills background exiftool runs
+    pub fn cancel_loading(&mut self) {
+        if let Some(loading) = self.loading.take() {
+            loading.jobs.into_iter().for_each(EtJob::kill);
+        }
+    }
+
+    /// Reads input right away, without showing loading screen
+    pub fn read_multiple_files(&mut self) -> std::io::Result<()> {
+        self.start_loading()?;
+        self.finish_loading()
+    }
+
+    /// Re-reads metadata of all loaded files, e.g. after they were modified by exiftool
+    pub fn reload_files(&mut self) -> std::io::Result<()> {
+        let (snapshots, files): (Vec<_>, Vec<_>) = self.et_data.iter().partition(|et| et.snapshot);
+        let files = files
+            .iter()
+            .map(|et| et.file_name.clone())
+            .collect::<Vec<_>>();
+        let mut et_data = if files.is_empty() {
+            Vec::new()
+        } else {
+            et_wrapper::run(&self.exiftool, files, false, self.config.structured_xmp)?
+        };
+        // Snapshots never change, they stay after files just like when they were added
+        et_data.extend(snapshots.into_iter().cloned());
+        if et_data.is_empty() {
+            return Err(std::io::Error::other(
+                "None of the files could be read again",
+            ));
+        }
+        let old_data = std::mem::replace(&mut self.et_data, et_data);
+        self.record_value_changes(&old_data);
+        self.refresh_file_labels();
+        // Files may vanish or move after reload, indices are matched by file name
+        let new_index = |old: usize| {
+            let file_name = &old_data.get(old)?.file_name;
+            self.et_data.iter().position(|f| &f.file_name == file_name)
+        };
+        self.current_file_index = new_index(self.current_file_index).unwrap_or(0);
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.compare_data.files = self
+            .compare_data
+            .files
+            .iter()
+            .filter_map(|&i| new_index(i))
+            .collect();
+        self.reference = self
+            .reference
+            .take()
+            .and_then(|(index, _)| new_index(index))
+            .map(|index| {
+                let entries = self.et_data[index]
+                    .tag_entries
+                    .iter()
+                    .map(|e| (e.as_key(), e.clone()))
+                    .collect();
+                (index, entries)
+            });
+        self.binary_previews.clear();
+        self.preview_job = None;
+        self.geolocations.clear();
+        if self.is_multiple_files() {
+            self.calculate_compare_data();
+        }
+        self.clamp_cursor();
+        Ok(())
+    }
+
+    /// Remembers previous values of tags which differ after reload and marks them as changed
+    fn record_value_changes(&mut self, old_data: &[ExiftoolEntry]) {
+        let mut changed = HashSet::new();
+        for file in &self.et_data {
+            let Some(old_file) = old_data.iter().find(|f| f.file_name == file.file_name) else {
+                continue;
+            };
+            let old_values = old_file
+                .tag_entries
+                .iter()
+                .map(|e| (e.as_key(), e.val.to_string()))
+                .collect::<HashMap<_, _>>();
+            for entry in &file.tag_entries {
+                let old_value = old_values.get(&entry.as_key());
+                if old_value == Some(&entry.val.to_string()) {
+                    continue;
+                }
+                let key = (file.file_name.clone(), entry.as_key());
+                let history = self.value_history.entry(key.clone()).or_default();
+                history.push(
+                    old_value
+                        .cloned()
+                        .unwrap_or_else(|| String::from("<not present>")),
+                );
+                if history.len() > VALUE_HISTORY_LEN {
+                    history.remove(0);
+                }
+                changed.insert(key);
+            }
+        }
+        self.last_reload_changes = Some((Instant::now(), changed));
+    }
+
+    /// Whether tag of current file got a new value on a recent reload
+    pub fn changed_on_reload(&self, entry: &TagEntry) -> bool {
+        self.last_reload_changes
+            .as_ref()
+            .is_some_and(|(time, changed)| {
+                time.elapsed() < CHANGE_HIGHLIGHT
+                    && changed.contains(&(self.current_file.clone(), entry.as_key()))
+            })
+    }
+
+    /// Called on every tick of main loop, expires time-based content.
+    /// Tells whether screen needs a redraw because of it
+    pub fn tick(&mut self) -> bool {
+        let highlight_expired = self
+            .last_reload_changes
+            .as_ref()
+            .is_some_and(|(time, _)| time.elapsed() >= CHANGE_HIGHLIGHT);
+        if highlight_expired {
+            self.last_reload_changes = None;
+        }
+        highlight_expired
+    }
+
+    /// Values tag of current file had before reloads, oldest first
+    pub fn previous_values(&self, entry: &TagEntry) -> &[String] {
+        self.value_history
+            .get(&(self.current_file.clone(), entry.as_key()))
+            .map(|v| v.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Switches between flattened and structured XMP, which needs files to be read again
+    pub fn toggle_structured_xmp(&mut self) {
+        self.config.structured_xmp = !self.config.structured_xmp;
+        self.log_msg = Some(match self.reload_files() {
+            Ok(()) if self.config.structured_xmp => Ok(String::from(
+                "XMP structures are now shown as nested values",
+            )),
+            Ok(()) => Ok(String::from("XMP structures are now flattened")),
+            Err(e) => Err(format!("Failed to reload files: {e}")),
+        });
+    }
+
+    /// Reports an error if files can't be modified because of read-only mode
+    fn check_writable(&mut self) -> bool {
+        if self.config.read_only {
+            self.log_msg = Some(Err(String::from(
+                "Read-only mode is on, files are not modified!",
+            )));
+        }
+        !self.config.read_only
+    }
+
+    /// Puts write action up for confirmation, see `confirm_write`
+    fn request_write(&mut self, action: WriteAction) {
+        if self.check_writable() {
+            self.pending_write = Some(action);
+            if self.write_commands().is_empty() {
+                self.pending_write = None;
+                self.log_msg = Some(Err(String::from(
+                    "Snapshots are not files on disk, they can't be modified!",
+                )));
+            }
+        }
+    }
+
+    /// Exiftool invocations (args and files) that pending write will run, snapshots are left out
+    pub fn write_commands(&self) -> Vec<(Vec<String>, Vec<PathBuf>)> {
+        let Some(action) = &self.pending_write else {
+            return Vec::new();
+        };
+        let commands = match action {
+            WriteAction::Geotag(track) => vec![(
+                vec![format!("-geotag={}", track.display())],
+                self.et_data
+                    .iter()
+                    .filter(|et| !et.snapshot)
+                    .map(|et| et.file_name.clone())
+                    .collect(),
+            )],
+            WriteAction::TimeShift(arg) => vec![(vec![arg.clone()], self.write_targets())],
+            WriteAction::Strip => vec![(vec![String::from("-all=")], self.write_targets())],
+            WriteAction::CopyTags { source, .. } if self.et_data[*source].snapshot => Vec::new(),
+            WriteAction::CopyTags { source, targets } => {
+                let args = vec![
+                    String::from("-tagsFromFile"),
+                    self.et_data[*source].file_name.display().to_string(),
+                    String::from("-all:all"),
+                ];
+                targets
+                    .iter()
+                    .filter(|&&target| !self.et_data[target].snapshot)
+                    .map(|&target| (args.clone(), vec![self.et_data[target].file_name.clone()]))
+                    .collect()
+            }
+            WriteAction::SetTag { tag, value } => {
+                vec![(vec![format!("-{tag}={value}")], self.write_targets())]
+            }
+        };
+        commands
+            .into_iter()
+            .filter(|(_, files)| !files.is_empty())
+            .map(|(mut args, files)| {
+                if self.overwrite_original {
+                    args.insert(0, String::from("-overwrite_original"));
+                }
+                (args, files)
+            })
+            .collect()
+    }
+
+    /// Opens dialog to type a new value of selected tag, prefilled with the current one
+    pub fn open_value_edit(&mut self) -> bool {
+        if !self.check_writable() {
+            return false;
+        }
+        let Some(entry) = self.selected_entry() else {
+            return false;
+        };
+        if entry.binary_size_kb.is_some() {
+            self.log_msg = Some(Err(String::from("Binary data can't be edited as text!")));
+            return false;
+        }
+        // Shown value joins list items, writing it back would turn the list into one item
+        if matches!(entry.val, EtVal::Array(_) | EtVal::Object(_)) {
+            self.log_msg = Some(Err(String::from(
+                "Lists and structures can't be edited as text!",
+            )));
+            return false;
+        }
+        let value = entry.val.to_string();
+        self.value_edit_dialog = Some(ValueEditDialog {
+            tag: entry.tag_arg(),
+            value: value.clone(),
+            original: value,
+        });
+        true
+    }
+
+    /// Puts value typed in edit dialog up for confirmation, it's written to current file only
+    pub fn request_value_edit(&mut self) {
+        let Some(dialog) = self.value_edit_dialog.take() else {
+            return;
+        };
+        if dialog.value == dialog.original {
+            self.log_msg = Some(Ok(format!("{} is unchanged", dialog.tag)));
+            return;
+        }
+        self.write_to_all = false;
+        self.request_write(WriteAction::SetTag {
+            tag: dialog.tag,
+            value: dialog.value,
+        });
+    }
+
+    /// Runs confirmed write, one exiftool per command, then reloads metadata to show the result.
+    /// Writes touching several files fill `batch_results`
+    pub fn confirm_write(&mut self) {
+        let commands = self.write_commands();
+        self.pending_write = None;
+        if commands.is_empty() || !self.check_writable() {
+            return;
+        }
+
+        let mut results = Vec::new();
+        for (args, files) in &commands {
+            match et_wrapper::write(&self.exiftool, args, files) {
+                Ok(file_results) => results.extend(
+                    files
+                        .iter()
+                        .zip(file_results)
+                        .map(|(file, res)| BatchResult::new(file.clone(), res)),
+                ),
+                Err(e) => results.extend(
+                    files
+                        .iter()
+                        .map(|file| BatchResult::new(file.clone(), Err(e.clone()))),
+                ),
+            }
+        }
+        let count = |outcome: WriteOutcome| results.iter().filter(|r| r.outcome == outcome).count();
+        let failed = count(WriteOutcome::Failed);
+        let message = match results.as_slice() {
+            [result] => match result.outcome {
+                WriteOutcome::Failed => format!("exiftool failed: {}", result.message),
+                _ => result.message.clone(),
+            },
+            _ => format!(
+                "{} file(s) updated, {} unchanged, {failed} failed",
+                count(WriteOutcome::Updated),
+                count(WriteOutcome::Unchanged)
+            ),
+        };
+        if results.len() > 1 {
+            self.batch_results = results;
+            self.batch_scroll = 0;
+        }
+        let reload = self.reload_files();
+        self.log_msg = Some(match reload {
+            Err(e) => Err(format!("{message}; failed to reload metadata: {e}")),
+            Ok(_) if failed > 0 => Err(message),
+            Ok(_) => Ok(message),
+        });
+    }
+
+    /// Whether pending write can be switched between current and all loaded files
+    pub fn pending_write_is_scoped(&self) -> bool {
+        matches!(
+            self.pending_write,
+            Some(WriteAction::TimeShift(_) | WriteAction::Strip | WriteAction::SetTag { .. })
+        ) && self.is_multiple_files()
+    }
+
+    /// Writes GPS tags of all loaded files from GPX/NMEA/KML track in `geotag_input`
+    pub fn request_geotag(&mut self) {
+        let track = PathBuf::from(self.geotag_input.trim());
+        if !track.is_file() {
+            self.log_msg = Some(Err(format!("Track file {} not found!", track.display())));
+            return;
+        }
+        self.request_write(WriteAction::Geotag(track));
+    }
+
+    /// Shifts all date/time tags of current or all loaded files by offset in `time_shift_input`
+    pub fn request_time_shift(&mut self) {
+        match et_wrapper::time_shift_arg(&self.time_shift_input) {
+            Ok(arg) => self.request_write(WriteAction::TimeShift(arg)),
+            Err(e) => self.log_msg = Some(Err(e)),
+        }
+    }
+
+    /// Files write actions apply to, see `write_to_all`
+    pub fn write_targets(&self) -> Vec<PathBuf> {
+        if self.write_to_all {
+            self.et_data
+                .iter()
+                .filter(|et| !et.snapshot)
+                .map(|et| et.file_name.clone())
+                .collect()
+        } else if self.et_data[self.current_file_index].snapshot {
+            Vec::new()
+        } else {
+            vec![self.current_file.clone()]
+        }
+    }
+
+    /// Removes all writable metadata from current or all loaded files
+    pub fn request_strip(&mut self) {
+        self.request_write(WriteAction::Strip);
+    }
+
+    /// Prepares copy tags picker with current file as the source and no targets
+    pub fn open_copy_tags_picker(&mut self) {
+        self.copy_tags_source = self.current_file_index;
+        self.copy_tags_targets = vec![false; self.et_data.len()];
+        self.copy_tags_cursor = 0;
+    }
+
+    /// Picks file under cursor as a target, or as the source if `as_source` is set
+    pub fn pick_copy_tags_file(&mut self, as_source: bool) {
+        let index = self.copy_tags_cursor;
+        if as_source {
+            self.copy_tags_source = index;
+            self.copy_tags_targets[index] = false;
+        } else if index != self.copy_tags_source {
+            self.copy_tags_targets[index] = !self.copy_tags_targets[index];
+        }
+    }
+
+    /// Switches compare view on or off, first compared file becomes the current one
+    pub fn toggle_compare_mode(&mut self) {
+        if self.compare_data.mode.is_on() {
+            self.compare_data.mode = CompareMode::Off;
+        } else {
+            self.compare_data.mode = CompareMode::All;
+        }
+        self.scroll_offset = (0, 0);
+        self.cursor = 0;
+        // Single file mode has no compare data
+        self.current_file_index = self
+            .compare_data
+            .files
+            .first()
+            .copied()
+            .unwrap_or(self.current_file_index);
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.clamp_cursor();
+    }
+
+    pub fn open_compare_picker(&mut self) {
+        self.compare_picks = vec![false; self.et_data.len()];
+        for &i in &self.compare_data.files {
+            self.compare_picks[i] = true;
+        }
+        self.compare_picks_cursor = 0;
+    }
+
+    /// Picks all files, or none if all are already picked
+    pub fn pick_all_compare_files(&mut self) {
+        let all = self.compare_picks.iter().all(|&p| p);
+        self.compare_picks.fill(!all);
+    }
+
+    /// Recomputes compare data from picked files, returns whether there were enough of them
+    pub fn apply_compare_picks(&mut self) -> bool {
+        let files = (0..self.et_data.len())
+            .filter(|&i| self.compare_picks[i])
+            .collect::<Vec<_>>();
+        if files.len() < 2 {
+            self.log_msg = Some(Err(String::from("Pick at least two files to compare!")));
+            return false;
+        }
+        self.log_msg = Some(Ok(format!(
+            "Comparing {} of {} files",
+            files.len(),
+            self.et_data.len()
+        )));
+        self.compare_data.files = files;
+        self.calculate_compare_data();
+        if !self.compare_data.files.contains(&self.current_file_index) {
+            self.current_file_index = self.compare_data.files[0];
+            self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        }
+        self.clamp_cursor();
+        true
+    }
+
+    /// Column of current file in compare data, if it takes part in compare
+    pub fn compare_column(&self) -> Option<usize> {
+        self.compare_data
+            .files
+            .iter()
+            .position(|&i| i == self.current_file_index)
+    }
+
+    /// Switches to the next (or previous) loaded file, in compare mode only to compared ones
+    pub fn step_current_file(&mut self, forward: bool) {
+        let files = if self.compare_data.mode.is_on() {
+            self.compare_data.files.clone()
+        } else {
+            (0..self.et_data.len()).collect()
+        };
+        let pos = files
+            .iter()
+            .position(|&i| i == self.current_file_index)
+            .unwrap_or_default();
+        let next = if forward {
+            (pos + 1) % files.len()
+        } else {
+            (pos + files.len() - 1) % files.len()
+        };
+        self.current_file_index = files[next];
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.clamp_cursor();
+    }
+
+    /// Switches to the next (or previous) compared file where selected tag has another value
+    /// than in current file, or is there at all if current file lacks it
+    pub fn step_to_file_with_tag(&mut self, forward: bool) {
+        let Some(key) = self.selected_key() else {
+            return;
+        };
+        let Some(values) = self
+            .compare_data
+            .data
+            .iter()
+            .find(|row| row.0.as_key() == key)
+            .map(|row| &row.1)
+        else {
+            return;
+        };
+        let Some(pos) = self.compare_column() else {
+            self.log_msg = Some(Err(String::from(
+                "Current file doesn't take part in compare!",
+            )));
+            return;
+        };
+        let num_files = values.len();
+        let next = (1..num_files)
+            .map(|step| {
+                if forward {
+                    (pos + step) % num_files
+                } else {
+                    (pos + num_files - step) % num_files
+                }
+            })
+            .find(|&col| values[col].is_some() && values[col] != values[pos]);
+        let Some(col) = next else {
+            self.log_msg = Some(Err(format!(
+                "No other file has a different {}!",
+                key.short_name
+            )));
+            return;
+        };
+        self.current_file_index = self.compare_data.files[col];
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        // Rows of compare view are shared by all files, tag list has to find the tag again
+        if self.compare_data.mode.is_off() {
+            let index = self.visible_entries().position(|e| e.as_key() == key);
+            self.cursor = index.unwrap_or(self.cursor);
+        }
+        self.clamp_cursor();
+    }
+
+    /// Copies all tags from picked source to each picked target
+    pub fn request_copy_tags(&mut self) {
+        let targets = (0..self.et_data.len())
+            .filter(|&i| self.copy_tags_targets[i])
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            self.log_msg = Some(Err(String::from("No target files picked!")));
+            return;
+        }
+        self.request_write(WriteAction::CopyTags {
+            source: self.copy_tags_source,
+            targets,
+        });
+    }
+
+    pub fn scrollv(&mut self, delta: i8) {
+        if self.rows_expanded() {
+            self.step_rows(delta);
+            return;
+        }
+        if delta < 0 {
+            self.cursor = self.cursor.saturating_sub(-delta as usize);
+        } else {
+            self.cursor = self.cursor.saturating_add(delta as usize);
+            self.cursor = self.cursor.min(self.num_entries_shown.saturating_sub(1));
+        }
+    }
+
+    /// Updates hovered entry from mouse position, tells whether it changed
+    pub fn hover(&mut self, column: u16, row: u16) -> bool {
+        // Inside of tag list borders
+        let area = self.main_area;
+        let inside =
+            column > area.x && column + 1 < area.right() && row > area.y && row + 1 < area.bottom();
+        let hovered = inside
+            .then(|| {
+                let mut list_row = (row - area.y - 1 + self.scroll_offset.0) as usize;
+                self.entry_rows_shown.iter().position(|&rows| {
+                    let is_hovered = list_row < rows;
+                    list_row = list_row.saturating_sub(rows);
+                    is_hovered
+                })
+            })
+            .flatten();
+        let changed = hovered != self.hovered;
+        self.hovered = hovered;
+        changed
+    }
+
+    pub fn scrollv_drag_cursor(&mut self, delta: i8) {
+        if self.rows_expanded() {
+            self.step_rows(delta);
+            return;
+        }
+        if delta < 0 {
+            self.scroll_offset.0 = self.scroll_offset.0.saturating_sub(-delta as u16);
+            self.cursor = self.cursor.saturating_sub(-delta as usize);
+        } else {
+            self.scroll_offset.0 = self.scroll_offset.0.saturating_add(delta as u16);
+            self.cursor = self.cursor.saturating_add(delta as usize);
+            self.cursor = self.cursor.min(self.num_entries_shown.saturating_sub(1));
+        }
+    }
+
+    /// Moves cursor by a page of tag list
+    pub fn scroll_page(&mut self, forward: bool) {
+        let page = self.rows_in_viewport.max(1);
+        if self.rows_expanded() {
+            for _ in 0..page {
+                self.step_rows(if forward { 1 } else { -1 });
+            }
+        } else if forward {
+            self.cursor = (self.cursor + page).min(self.num_entries_shown.saturating_sub(1));
+        } else {
+            self.cursor = self.cursor.saturating_sub(page);
+        }
+    }
+
+    /// Moves cursor to the first or the last shown entry
+    pub fn scroll_to_end(&mut self, last: bool) {
+        self.cursor = if last {
+            self.num_entries_shown.saturating_sub(1)
+        } else {
+            0
+        };
+        let rows = self.entry_rows_shown.get(self.cursor).copied().unwrap_or(1);
+        self.array_cursor = (self.cursor, if last { rows - 1 } else { 0 });
+    }
+
+    /// Whether some entries of tag list take several rows, so cursor goes through their elements
+    fn rows_expanded(&self) -> bool {
+        self.entry_rows_shown.iter().any(|&rows| rows > 1)
+    }
+
+    /// Moves cursor by rows instead of entries, stepping through elements of vertical arrays
+    fn step_rows(&mut self, delta: i8) {
+        let rows = |i: usize| self.entry_rows_shown.get(i).copied().unwrap_or(1);
+        for _ in 0..delta.unsigned_abs() {
+            let element = self.array_element();
+            if delta > 0 {
+                if element + 1 < rows(self.cursor) {
+                    self.array_cursor = (self.cursor, element + 1);
+                } else if self.cursor + 1 < self.num_entries_shown {
+                    self.cursor += 1;
+                    self.array_cursor = (self.cursor, 0);
+                }
+            } else if element > 0 {
+                self.array_cursor = (self.cursor, element - 1);
+            } else if self.cursor > 0 {
+                self.cursor -= 1;
+                self.array_cursor = (self.cursor, rows(self.cursor) - 1);
+            }
+        }
+    }
+
+    /// Selected element of array under cursor while arrays are shown vertically
+    pub fn array_element(&self) -> usize {
+        if self.array_cursor.0 == self.cursor {
+            self.array_cursor.1
+        } else {
+            0
+        }
+    }
+
+    /// Rows an entry takes in tag list, arrays take one per element while shown vertically
+    pub fn entry_rows(&self, entry: &TagEntry) -> usize {
+        match entry.display_val(self.data_display_mode.numerical) {
+            EtVal::Array(vec) if self.config.vertical_arrays && entry.binary_size_kb.is_none() => {
+                vec.len().max(1)
+            }
+            _ => 1,
+        }
+    }
+
+    pub fn scrollh(&mut self, delta: i8) {
+        if delta < 0 {
+            self.scroll_offset.1 = self.scroll_offset.1.saturating_sub(-delta as u16);
+        } else {
+            self.scroll_offset.1 = self.scroll_offset.1.saturating_add(delta as u16);
+        }
+    }
+
+    /// Scrolls horizontally, speeding up while the arrow key is being held
+    pub fn scrollh_accelerated(&mut self, direction: i8) {
+        let now = Instant::now();
+        self.hscroll_step = match self.last_hscroll {
+            Some((time, last_direction))
+                if last_direction == direction && now - time < HSCROLL_REPEAT_WINDOW =>
+            {
+                (self.hscroll_step + 1).min(HSCROLL_MAX_STEP)
+            }
+            _ => 1,
+        };
+        self.last_hscroll = Some((now, direction));
+        self.scrollh(direction * self.hscroll_step);
+    }
+
+    /// Moves cursor to the 1-based visible entry index
+    pub fn goto_entry(&mut self, index: usize) {
+        if index >= 1 && index <= self.num_entries_shown {
+            self.cursor = index - 1;
+        } else {
+            self.log_msg = Some(Err(format!(
+                "Entry index should be between 1 and {}!",
+                self.num_entries_shown
+            )));
+        }
+    }
+
+    /// Completes last word of command line, repeated calls cycle through candidates
+    pub fn complete_command(&mut self) {
+        if let Some((candidates, index)) = &mut self.command_completion {
+            if candidates.get(*index) == Some(&self.command_input) {
+                *index = (*index + 1) % candidates.len();
+                self.command_input = candidates[*index].clone();
+                return;
+            }
+        }
+        let entries = &self.et_data[self.current_file_index].tag_entries;
+        let tags = entries
+            .iter()
+            .map(|e| e.short_name.clone())
+            .collect::<Vec<_>>();
+        let families = entries
+            .iter()
+            .flat_map(|e| [e.table.0.clone(), e.table_to_string()])
+            .collect::<Vec<_>>();
+        let candidates = command::completions(&self.command_input, &tags, &families);
+        if let Some(first) = candidates.first() {
+            self.command_input = first.clone();
+            self.command_completion = Some((candidates, 0));
+        }
+    }
+
+    /// Runs action typed in command line, `Quit` is left for the caller
+    pub fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Goto(index) => self.goto_entry(index),
+            Action::Filter(filter) => {
+                self.filter = filter;
+                self.clamp_cursor();
+            }
+            Action::Export { format, path } => match format.as_str() {
+                "xlsx" if self.compare_data.mode.is_on() => self.export_compare_xlsx(path),
+                "xlsx" => {
+                    self.log_msg = Some(Err(String::from(
+                        "Spreadsheet export is only available in compare mode!",
+                    )))
+                }
+                "csv" if self.compare_data.mode.is_on() => self.export_compare_csv(path),
+                "csv" => {
+                    self.log_msg = Some(Err(String::from(
+                        "CSV export is only available in compare mode!",
+                    )))
+                }
+                "json" => self.export_tags_json(path),
+                _ => self.log_msg = Some(Err(format!("Unknown export format '{format}'!"))),
+            },
+            Action::Write { tag, value } => self.request_write(WriteAction::SetTag { tag, value }),
+            Action::Sort(mode) => self.set_sort_mode(mode),
+            Action::Quit => {}
+        }
+    }
+
+    /// Number of exiftool's warnings and errors left out of tag list, of current or all compared files
+    pub fn hidden_messages(&self) -> usize {
+        if !self.config.hide_warnings {
+            return 0;
+        }
+        let files = if self.compare_data.mode.is_on() {
+            self.compare_data.files.clone()
+        } else {
+            vec![self.current_file_index]
+        };
+        files
+            .iter()
+            .flat_map(|&i| &self.et_data[i].tag_entries)
+            .filter(|e| e.is_exiftool_message())
+            .count()
+    }
+
+    /// Moves cursor to the next (or previous) shown warning/error, wrapping around
+    pub fn jump_to_problem(&mut self, forward: bool) {
+        let problems = if self.compare_data.mode.is_on() {
+            self.visible_compare_rows()
+                .map(|row| row.0.is_warning_or_error())
+                .collect::<Vec<_>>()
+        } else {
+            self.visible_entries()
+                .map(|entry| entry.is_warning_or_error())
+                .collect::<Vec<_>>()
+        };
+        let len = problems.len();
+        let next = (1..=len)
+            .map(|step| {
+                if forward {
+                    (self.cursor + step) % len
+                } else {
+                    (self.cursor + len - step) % len
+                }
+            })
+            .find(|&i| problems[i]);
+        match next {
+            Some(index) => self.cursor = index,
+            None if self.hidden_messages() > 0 => {
+                self.log_msg = Some(Err(String::from(
+                    "Warnings and errors are hidden, set `hide_warnings = false` to show them!",
+                )))
+            }
+            None => self.log_msg = Some(Err(String::from("No warnings or errors shown!"))),
+        }
+    }
+
+    /// Starts typing a search, previous one is dropped
+    pub fn open_search(&mut self) {
+        self.search_query.clear();
+        self.search_origin = self.cursor;
+    }
+
+    /// Moves cursor to the first match of search typed so far, counting from where search started
+    pub fn update_search(&mut self) {
+        self.cursor = self.search_origin;
+        if !self.search_query.is_empty() {
+            self.step_to_match(true, 0);
+        }
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search_query.clear();
+        self.cursor = self.search_origin;
+    }
+
+    /// Moves cursor to the next (or previous) tag matching search, wrapping around
+    pub fn search_step(&mut self, forward: bool) {
+        self.step_to_match(forward, 1);
+    }
+
+    /// Searches rows from `first_step` rows away from cursor, a tag matches the way filter would match it
+    fn step_to_match(&mut self, forward: bool, first_step: usize) {
+        let query = &self.search_query;
+        let match_family = self.config.filter_matches_family;
+        let matches = if self.compare_data.mode.is_on() {
+            self.visible_compare_rows()
+                .map(|(_, values)| {
+                    values
+                        .iter()
+                        .flatten()
+                        .any(|v| v.check_filter(query, match_family))
+                })
+                .collect::<Vec<_>>()
+        } else {
+            self.visible_entries()
+                .map(|entry| entry.check_filter(query, match_family))
+                .collect::<Vec<_>>()
+        };
+        let len = matches.len();
+        let next = (first_step..len + first_step)
+            .map(|step| {
+                if forward {
+                    (self.cursor + step) % len
+                } else {
+                    (self.cursor + len - step) % len
+                }
+            })
+            .find(|&i| matches[i]);
+        match next {
+            Some(index) => {
+                self.cursor = index;
+                self.array_cursor = (index, 0);
+            }
+            None => self.log_msg = Some(Err(format!("No tags match '{query}'"))),
+        }
+    }
+
+    /// Entries of current file which pass the filter, in order of `sort_mode`
+    pub fn visible_entries(&self) -> impl Iterator<Item = &TagEntry> {
+        let mut entries = self.et_data[self.current_file_index]
+            .tag_entries
+            .iter()
+            .filter(|ee| !self.config.hide_warnings || !ee.is_exiftool_message())
+            .filter(|ee| {
+                self.filter.is_empty()
+                    || ee.check_filter(&self.filter, self.config.filter_matches_family)
+            })
+            .filter(|ee| {
+                !self.reference_diff_only || self.differs_from_reference(ee) != Some(false)
+            })
+            .filter(|ee| !self.essentials_only || self.is_essential(ee))
+            .collect::<Vec<_>>();
+        if self.sort_mode != SortMode::None {
+            entries
+                .sort_by_cached_key(|e| self.sort_mode.sort_key(e, self.data_display_mode.short));
+        }
+        entries.into_iter()
+    }
+
+    /// Compare view rows which pass the filter and the diff check
+    pub fn visible_compare_rows(&self) -> impl Iterator<Item = &(TagEntry, Vec<Option<TagEntry>>)> {
+        let mode = self.compare_data.mode;
+        let check_filter = |v: &Vec<Option<TagEntry>>| {
+            self.filter.is_empty()
+                || v.iter().any(|v| {
+                    v.as_ref().is_some_and(|v| {
+                        v.check_filter(&self.filter, self.config.filter_matches_family)
+                    })
+                })
+        };
+
+        let check_diff = move |(k, v): &(TagEntry, Vec<Option<TagEntry>>)| {
+            let differ = || match self.compare_data.expected.get(&k.as_key()) {
+                Some(expected) => !v
+                    .iter()
+                    .all(|e| analysis::matches_expected(e.as_ref(), expected)),
+                None => analysis::values_differ(v),
+            };
+            match mode {
+                CompareMode::DiffOnly => differ(),
+                CompareMode::MatchesOnly => !differ(),
+                _ => true,
+            }
+        };
+
+        let mut rows = self
+            .compare_data
+            .data
+            .iter()
+            .filter(|ee| !self.config.hide_warnings || !ee.0.is_exiftool_message())
+            .filter(move |ee| check_filter(&ee.1) && check_diff(ee))
+            .filter(|ee| !self.essentials_only || self.is_essential(&ee.0))
+            .collect::<Vec<_>>();
+        if self.sort_mode != SortMode::None {
+            rows.sort_by_cached_key(|row| {
+                self.sort_mode
+                    .sort_key(&row.0, self.data_display_mode.short)
+            });
+        }
+        rows.into_iter()
+    }
+
+    /// Switches to the next tag order, cursor stays on the same tag
+    pub fn cycle_sort_mode(&mut self) {
+        self.set_sort_mode(self.sort_mode.next());
+    }
+
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        let key = self.selected_key();
+        self.sort_mode = mode;
+        let index = key.and_then(|key| {
+            if self.compare_data.mode.is_on() {
+                self.visible_compare_rows()
+                    .position(|row| row.0.as_key() == key)
+            } else {
+                self.visible_entries().position(|e| e.as_key() == key)
+            }
+        });
+        self.cursor = index.unwrap_or(0);
+        self.array_cursor = (self.cursor, 0);
+        self.log_msg = Some(Ok(format!("Tags sorted by: {}", mode.label())));
+    }
+
+    /// Saves current filter as a new view and makes it active
+    pub fn add_view(&mut self) {
+        if self.view_name.is_empty() {
+            self.log_msg = Some(Err(String::from("View name can't be empty!")));
+            return;
+        }
+        self.views.push(View {
+            name: std::mem::take(&mut self.view_name),
+            filter: self.filter.clone(),
+            cursor: self.cursor,
+        });
+        self.active_view = Some(self.views.len() - 1);
+    }
+
+    pub fn remove_active_view(&mut self) {
+        if let Some(index) = self.active_view.take() {
+            let view = self.views.remove(index);
+            self.log_msg = Some(Ok(format!("Removed view '{}'", view.name)));
+        }
+    }
+
+    /// Stores filter and cursor into active view and restores them from the next one
+    pub fn switch_view(&mut self, delta: isize) {
+        if self.views.is_empty() {
+            self.log_msg = Some(Err(String::from(
+                "There are no views, press <v> to save current filter as one",
+            )));
+            return;
+        }
+        if let Some(view) = self.active_view.and_then(|i| self.views.get_mut(i)) {
+            view.filter = self.filter.clone();
+            view.cursor = self.cursor;
+        }
+        let next = match self.active_view {
+            Some(index) => (index as isize + delta).rem_euclid(self.views.len() as isize) as usize,
+            None if delta < 0 => self.views.len() - 1,
+            None => 0,
+        };
+        self.active_view = Some(next);
+        self.filter = self.views[next].filter.clone();
+        self.cursor = self.views[next].cursor;
+        self.clamp_cursor();
+    }
+
+    pub fn build_tag_report(&mut self) {
+        self.tag_report = analysis::tag_frequencies(&self.compare_data.data);
+        analysis::sort_tag_frequencies(&mut self.tag_report, self.tag_report_sort);
+        self.tag_report_cursor = 0;
+    }
+
+    pub fn cycle_tag_report_sort(&mut self) {
+        self.tag_report_sort = self.tag_report_sort.next();
+        analysis::sort_tag_frequencies(&mut self.tag_report, self.tag_report_sort);
+        self.tag_report_cursor = 0;
+    }
+
+    /// Writes display toggles changed while running to config, so next launch looks the same
+    pub fn save_display_prefs(&self) -> Result<(), String> {
+        // Toggles kept outside of config are saved along with it
+        let prefs = DisplayPrefs {
+            compact_layout: self.compact_mode,
+            short_names: self.data_display_mode.short,
+            numerical_values: self.data_display_mode.numerical,
+            ..self.config.display_prefs()
+        };
+        if prefs == self.loaded_display_prefs {
+            return Ok(());
+        }
+        config::save_display_prefs(&prefs)
+    }
+
+    /// Has to be called whenever `et_data` changes
+    fn refresh_file_labels(&mut self) {
+        self.file_labels = file_labels(&self.et_data, &self.config.completeness_tags);
+    }
+
+    pub fn is_essential(&self, entry: &TagEntry) -> bool {
+        self.config.essential_tags.contains(&entry.short_name)
+    }
+
+    /// Moves focus between tag list and details pane, details always start scrolled to top
+    pub fn focus_pane(&mut self, pane: Pane) {
+        self.focus = if self.show_details { pane } else { Pane::List };
+        self.details_scroll = 0;
+    }
+
+    pub fn scroll_details(&mut self, delta: i16) {
+        self.details_scroll = self
+            .details_scroll
+            .saturating_add_signed(delta)
+            .min(self.details_scroll_max);
+    }
+
+    /// Key of selected tag, in compare mode also when current file lacks it
+    fn selected_key(&self) -> Option<TagEntryKey> {
+        if self.compare_data.mode.is_on() {
+            self.visible_compare_rows()
+                .nth(self.cursor)
+                .map(|row| row.0.as_key())
+        } else {
+            self.selected_entry().map(TagEntry::as_key)
+        }
+    }
+
+    pub fn is_marked(&self, entry: &TagEntry) -> bool {
+        self.marked.contains(&entry.as_key())
+    }
+
+    /// Whether entry goes to exports and copies, which take only marked tags while there are any
+    pub fn is_exported(&self, entry: &TagEntry) -> bool {
+        self.marked.is_empty() || self.is_marked(entry)
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(key) = self.selected_key() else {
+            return;
+        };
+        if !self.marked.remove(&key) {
+            self.marked.insert(key);
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.log_msg = Some(Ok(String::from(
+            "Marks cleared, exports take all shown tags again",
+        )));
+    }
+
+    /// Adds selected tag to essentials or removes it from there, saving the list to config
+    pub fn toggle_essential(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let short_name = entry.short_name.clone();
+        let tags = &mut self.config.essential_tags;
+        let added = if let Some(pos) = tags.iter().position(|t| *t == short_name) {
+            tags.remove(pos);
+            false
+        } else {
+            tags.push(short_name.clone());
+            true
+        };
+        self.log_msg = Some(
+            config::save_essential_tags(&self.config.essential_tags).map(|_| {
+                if added {
+                    format!("{short_name} added to essentials")
+                } else {
+                    format!("{short_name} removed from essentials")
+                }
+            }),
+        );
+        self.clamp_cursor();
+    }
+
+    /// Shows next (or previous) page of compare columns, its first file becomes the current one
+    pub fn page_compare_columns(&mut self, forward: bool) {
+        let page = self.compare_columns_shown.max(1);
+        let last_first = self.compare_data.files.len().saturating_sub(page);
+        self.compare_first_column = if forward {
+            (self.compare_first_column + page).min(last_first)
+        } else {
+            self.compare_first_column.saturating_sub(page)
+        };
+        self.current_file_index = self.compare_data.files[self.compare_first_column];
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.clamp_cursor();
+    }
+
+    pub fn build_diff_summary(&mut self) {
+        self.diff_summary =
+            analysis::diff_summary(&self.compare_data.data, self.compare_data.files.len());
+        for summary in &mut self.diff_summary {
+            summary.file_index = self.compare_data.files[summary.file_index];
+        }
+        self.diff_summary_cursor = 0;
+    }
+
+    /// Makes file under diff summary cursor the current one
+    pub fn open_diff_summary_file(&mut self) {
+        if let Some(summary) = self.diff_summary.get(self.diff_summary_cursor) {
+            self.current_file_index = summary.file_index;
+            self.current_file = self.et_data[self.current_file_index].file_name.clone();
+            self.clamp_cursor();
+        }
+    }
+
+    pub fn build_exposure_table(&mut self) {
+        let files = self
+            .et_data
+            .iter()
+            .map(|et| et.tag_entries.as_slice())
+            .collect::<Vec<_>>();
+        self.exposure_rows = analysis::exposure_table(&files);
+        self.exposure_cursor = 0;
+    }
+
+    /// Makes file under exposure table cursor the current one
+    pub fn open_exposure_file(&mut self) {
+        if let Some(row) = self.exposure_rows.get(self.exposure_cursor) {
+            self.current_file_index = row.file_index;
+            self.current_file = self.et_data[self.current_file_index].file_name.clone();
+            self.clamp_cursor();
+        }
+    }
+
+    /// Writes exposure table to Downloads, numbers in decimal form so spreadsheets can chart them
+    pub fn export_exposure_csv(&mut self) {
+        let Some(path) = self.unique_download_path("exposures", "csv") else {
+            self.log_msg = Some(Err(String::from("Failed to obtain a downloads dir!")));
+            return;
+        };
+        let mut rows = vec![["File", "Taken"]
+            .into_iter()
+            .chain(analysis::EXPOSURE_COLUMNS.iter().map(|(label, _)| *label))
+            .map(String::from)
+            .collect::<Vec<_>>()];
+        rows.extend(self.exposure_rows.iter().map(|row| {
+            [
+                self.et_data[row.file_index].file_name.display().to_string(),
+                row.taken.clone().unwrap_or_default(),
+            ]
+            .into_iter()
+            .chain(row.numbers.iter().zip(&row.values).map(|(number, value)| {
+                number
+                    .map(|n| n.to_string())
+                    .or_else(|| value.clone())
+                    .unwrap_or_default()
+            }))
+            .collect()
+        }));
+        self.log_msg = Some(match std::fs::write(&path, export::to_csv(&rows)) {
+            Ok(_) => Ok(format!("Succesfully exported at {}", path.display())),
+            Err(e) => Err(format!("Failed to export CSV: {e}")),
+        });
+    }
+
+    pub fn build_keyword_tree(&mut self) {
+        self.keyword_rows =
+            keywords::keyword_tree(&self.et_data[self.current_file_index].tag_entries);
+        self.keywords_cursor = 0;
+    }
+
+    pub fn build_gps_summary(&mut self) {
+        let file = &self.et_data[self.current_file_index];
+        self.gps = gps::gps_summary(&file.tag_entries);
+        self.gps_cursor = 0;
+        let has_place = self.gps.rows.iter().any(|row| row.label == "Place");
+        if self.config.geolocation && !file.snapshot && !has_place && self.gps.lat_long().is_some()
+        {
+            // Lookup runs exiftool, it's done once per file
+            if !self.geolocations.contains_key(&file.file_name) {
+                match et_wrapper::geolocation_tags(&self.exiftool, &file.file_name) {
+                    Ok(tags) => {
+                        self.geolocations.insert(file.file_name.clone(), tags);
+                    }
+                    Err(e) => self.log_msg = Some(Err(format!("Failed to look up place: {e}"))),
+                }
+            }
+            if let Some(tags) = self.geolocations.get(&file.file_name) {
+                self.gps.rows.extend(gps::place_row(tags));
+            }
+        }
+    }
+
+    /// Appends pasted text to filter, as a single line without surrounding whitespace
+    pub fn paste_filter(&mut self, text: &str) {
+        self.filter
+            .push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+        self.clamp_cursor();
+    }
+
+    /// Keeps cursor on an existing row after filter or current file have changed
+    pub fn clamp_cursor(&mut self) {
+        self.num_entries_shown = if self.compare_data.mode.is_on() {
+            self.visible_compare_rows().count()
+        } else {
+            self.visible_entries().count()
+        };
+        self.cursor = self.cursor.min(self.num_entries_shown.saturating_sub(1));
+    }
+
+    /// Will return a 'key entry' for compare view
+    pub fn selected_entry(&self) -> Option<&TagEntry> {
+        if self.compare_data.mode.is_on() {
+            let column = self.compare_column()?;
+            self.visible_compare_rows()
+                .map(|entry| entry.1[column].as_ref())
+                .nth(self.cursor)
+                .unwrap_or(None)
+        } else {
+            self.visible_entries().nth(self.cursor)
+        }
+    }
+
+    /// Starts typing expected value of selected compare row, previous one is kept for editing
+    pub fn open_expected_value(&mut self) -> bool {
+        let Some(key) = self
+            .visible_compare_rows()
+            .nth(self.cursor)
+            .map(|row| row.0.as_key())
+        else {
+            return false;
+        };
+        self.expected_input = self
+            .compare_data
+            .expected
+            .get(&key)
+            .cloned()
+            .unwrap_or_default();
+        true
+    }
+
+    /// Sets `expected_input` as expected value of selected compare row, empty input clears it
+    pub fn set_expected_value(&mut self) {
+        let Some((entry, values)) = self.visible_compare_rows().nth(self.cursor) else {
+            return;
+        };
+        let key = entry.as_key();
+        let expected = self.expected_input.trim().to_owned();
+        if expected.is_empty() {
+            self.compare_data.expected.remove(&key);
+            self.log_msg = Some(Ok(format!("Expected value of {} cleared", key.short_name)));
+        } else {
+            let matching = values
+                .iter()
+                .filter(|e| analysis::matches_expected(e.as_ref(), &expected))
+                .count();
+            self.log_msg = Some(Ok(format!(
+                "{} is '{expected}' in {matching} of {} files",
+                key.short_name,
+                values.len()
+            )));
+            self.compare_data.expected.insert(key, expected);
+        }
+        self.clamp_cursor();
+    }
+
+    pub fn reference_index(&self) -> Option<usize> {
+        self.reference.as_ref().map(|(i, _)| *i)
+    }
+
+    /// Makes current file the reference, or clears it if current file already is one
+    pub fn toggle_reference(&mut self) {
+        if self.reference_index() == Some(self.current_file_index) {
+            self.reference = None;
+            self.reference_diff_only = false;
+            self.log_msg = Some(Ok(String::from("Reference file cleared")));
+        } else {
+            let entries = self.et_data[self.current_file_index]
+                .tag_entries
+                .iter()
+                .map(|e| (e.as_key(), e.clone()))
+                .collect();
+            self.reference = Some((self.current_file_index, entries));
+            self.log_msg = Some(Ok(format!(
+                "{} is now the reference file",
+                self.current_file.display()
+            )));
+        }
+        self.clamp_cursor();
+    }
+
+    /// `None` when there is no reference or current file is the reference
+    pub fn differs_from_reference(&self, entry: &TagEntry) -> Option<bool> {
+        match &self.reference {
+            Some((index, entries)) if *index != self.current_file_index => {
+                Some(entries.get(&entry.as_key()) != Some(entry))
+            }
+            _ => None,
+        }
+    }
+
+    /// Closes current tab, keeping reference pointed at the same file
+    pub fn close_current_file(&mut self) {
+        let removed = self.current_file_index;
+        self.et_data.remove(removed);
+        self.refresh_file_labels();
+        match self.reference_index() {
+            Some(index) if index == removed => {
+                self.reference = None;
+                self.reference_diff_only = false;
+            }
+            Some(index) if index > removed => {
+                if let Some((index, _)) = &mut self.reference {
+                    *index -= 1;
+                }
+            }
+            _ => {}
         }
+        if self.current_file_index == self.et_data.len() {
+            self.current_file_index -= 1;
+        }
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.compare_data.files = self
+            .compare_data
+            .files
+            .iter()
+            .filter(|&&i| i != removed)
+            .map(|&i| if i > removed { i - 1 } else { i })
+            .collect();
+        self.calculate_compare_data();
+        self.clamp_cursor();
     }
-}
 
-#[derive(Default)]
-pub struct CompareData {
-    pub mode: Option<bool>,
-    pub data: Vec<(TagEntry, Vec<Option<TagEntry>>)>,
-}
+    pub fn is_compact(&self, width: u16) -> bool {
+        self.compact_mode
+            .unwrap_or(width < self.config.compact_width)
+    }
 
-pub struct MainState {
-    pub current_file: PathBuf,
-    pub show_details: bool,
-    pub binary_save_dialog: Option<BinarySaveDialog>,
-    pub filter: String,
-    pub num_entries_shown: usize,
-    pub et_data: Vec<ExiftoolEntry>,
-    pub current_file_index: usize,
-    pub data_display_mode: DataDisplayMode,
-    pub scroll_offset: (u16, u16),
-    pub cursor: usize,
-    user_dirs: UserDirs,
-    pub log_msg: Option<Result<String, String>>,
-    multiple_files_input: Option<Vec<PathBuf>>,
-    pub compare_data: CompareData,
-}
+    /// Cycles compact layout between automatic, forced on and forced off
+    pub fn cycle_compact_mode(&mut self) {
+        self.compact_mode = match self.compact_mode {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        self.log_msg = Some(Ok(String::from(match self.compact_mode {
+            None => "Compact layout: automatic",
+            Some(true) => "Compact layout: on",
+            Some(false) => "Compact layout: off",
+        })));
+    }
 
-impl MainState {
-    fn new(image_path: PathBuf) -> std::io::Result<Self> {
-        let et_data = et_wrapper::run(vec![image_path.clone()], false)?;
-        let num_entries_shown = et_data[0].tag_entries.len();
+    /// Every warning and error exiftool reported for current file, or for all files by file
+    pub fn warnings_text(&self, all_files: bool) -> Option<String> {
+        let files = if all_files {
+            &self.et_data[..]
+        } else {
+            std::slice::from_ref(&self.et_data[self.current_file_index])
+        };
+        let text = files
+            .iter()
+            .filter_map(|et| {
+                let lines = et
+                    .tag_entries
+                    .iter()
+                    .filter(|e| e.is_warning_or_error())
+                    .map(|e| format!("{}: {}", e.name, e.val))
+                    .collect::<Vec<_>>();
+                if lines.is_empty() {
+                    None
+                } else if all_files {
+                    Some(format!("{}\n{}", et.file_name.display(), lines.join("\n")))
+                } else {
+                    Some(lines.join("\n"))
+                }
+            })
+            .collect::<Vec<_>>();
+        (!text.is_empty()).then(|| text.join("\n\n"))
+    }
 
-        Ok(Self {
-            current_file: image_path,
-            show_details: false,
-            binary_save_dialog: None,
-            filter: String::new(),
-            num_entries_shown,
-            et_data,
-            current_file_index: 0,
-            data_display_mode: Default::default(),
-            scroll_offset: (0, 0),
-            cursor: 0,
-            user_dirs: UserDirs::new().expect("Failed to locate user home dir!"),
-            log_msg: None,
-            multiple_files_input: None,
-            compare_data: Default::default(),
-        })
+    /// Visible (or marked) tags of active file in compare mode as `tag\tvalue` lines
+    pub fn compare_column_text(&self) -> String {
+        let Some(column) = self.compare_column() else {
+            return String::new();
+        };
+        self.visible_compare_rows()
+            .filter(|(k, _)| self.is_exported(k))
+            .filter_map(|(_, values)| values[column].as_ref())
+            .map(|entry| self.tsv_line(entry))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
-    fn new_multiple_files(input: Vec<PathBuf>) -> Self {
-        Self {
-            current_file: PathBuf::new(),
-            show_details: false,
-            binary_save_dialog: None,
-            filter: String::new(),
-            num_entries_shown: 0,
-            et_data: Vec::new(),
-            current_file_index: 0,
-            data_display_mode: Default::default(),
-            scroll_offset: (0, 0),
-            cursor: 0,
-            user_dirs: UserDirs::new().expect("Failed to locate user home dir!"),
-            log_msg: None,
-            multiple_files_input: Some(input),
-            compare_data: Default::default(),
+    /// Visible (or marked) tags of current file as `tag\tvalue` lines, of active column in compare mode
+    pub fn shown_tags_text(&self) -> String {
+        if self.compare_data.mode.is_on() {
+            return self.compare_column_text();
         }
+        self.visible_entries()
+            .filter(|e| self.is_exported(e))
+            .map(|entry| self.tsv_line(entry))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
-    pub fn read_multiple_files(&mut self, recursive: bool) -> std::io::Result<()> {
-        let input_files = self.multiple_files_input.take().unwrap();
-        self.et_data = et_wrapper::run(input_files, recursive)?;
-        self.num_entries_shown = self.et_data[0].tag_entries.len();
-        self.current_file = self.et_data[0].file_name.clone();
-        self.calculate_compare_data();
-        Ok(())
+    /// Tag name as shown and its value, tab separated
+    fn tsv_line(&self, entry: &TagEntry) -> String {
+        format!(
+            "{}\t{}",
+            if self.data_display_mode.short {
+                &entry.short_name
+            } else {
+                &entry.name
+            },
+            entry.display_value(self.data_display_mode.numerical)
+        )
     }
 
-    pub fn scrollv(&mut self, delta: i8) {
-        if delta < 0 {
-            self.cursor = self.cursor.saturating_sub(-delta as usize);
+    /// Position of an entry with given key among visible rows
+    fn visible_index_of(&self, key: &TagEntryKey) -> Option<usize> {
+        if self.compare_data.mode.is_on() {
+            self.visible_compare_rows()
+                .position(|(k, _)| &k.as_key() == key)
         } else {
-            self.cursor = self.cursor.saturating_add(delta as usize);
-            self.cursor = self.cursor.min(self.num_entries_shown.saturating_sub(1));
+            self.visible_entries().position(|e| &e.as_key() == key)
         }
     }
 
-    pub fn scrollv_drag_cursor(&mut self, delta: i8) {
-        if delta < 0 {
-            self.scroll_offset.0 = self.scroll_offset.0.saturating_sub(-delta as u16);
-            self.cursor = self.cursor.saturating_sub(-delta as usize);
+    pub fn current_bookmarks(&self) -> &[Bookmark] {
+        self.storage.bookmarks(&self.current_file)
+    }
+
+    pub fn toggle_bookmark(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let bookmark = Bookmark {
+            key: entry.as_key(),
+            name: entry.name.clone(),
+        };
+        let added = self.storage.toggle_bookmark(&self.current_file, bookmark);
+        self.log_msg = Some(match self.storage.save() {
+            Ok(_) if added => Ok(String::from("Bookmark added")),
+            Ok(_) => Ok(String::from("Bookmark removed")),
+            Err(e) => Err(format!("Failed to save bookmarks: {e}")),
+        });
+    }
+
+    pub fn delete_selected_bookmark(&mut self) {
+        if let Some(bookmark) = self.current_bookmarks().get(self.bookmarks_cursor).cloned() {
+            self.storage.toggle_bookmark(&self.current_file, bookmark);
+            if let Err(e) = self.storage.save() {
+                self.log_msg = Some(Err(format!("Failed to save bookmarks: {e}")));
+            }
+            self.bookmarks_cursor = self
+                .bookmarks_cursor
+                .min(self.current_bookmarks().len().saturating_sub(1));
+        }
+    }
+
+    /// Moves cursor to the selected bookmark, resetting filter if it hides the entry
+    pub fn jump_to_selected_bookmark(&mut self) {
+        let Some(bookmark) = self.current_bookmarks().get(self.bookmarks_cursor).cloned() else {
+            return;
+        };
+        if self.visible_index_of(&bookmark.key).is_none() {
+            self.filter.clear();
+        }
+        if let Some(index) = self.visible_index_of(&bookmark.key) {
+            self.cursor = index;
         } else {
-            self.scroll_offset.0 = self.scroll_offset.0.saturating_add(delta as u16);
-            self.cursor = self.cursor.saturating_add(delta as usize);
-            self.cursor = self.cursor.min(self.num_entries_shown.saturating_sub(1));
+            self.clamp_cursor();
+            self.log_msg = Some(Err(format!(
+                "Bookmarked tag '{}' is not present in this file!",
+                bookmark.name
+            )));
         }
     }
 
-    pub fn scrollh(&mut self, delta: i8) {
-        if delta < 0 {
-            self.scroll_offset.1 = self.scroll_offset.1.saturating_sub(-delta as u16);
+    /// Whether current file is a metadata-only sidecar, e.g. `.xmp`
+    pub fn current_is_sidecar(&self) -> bool {
+        et_wrapper::is_sidecar(&self.current_file)
+    }
+
+    /// Why binary data can't be extracted from current file, if it holds metadata only
+    pub fn binary_unavailable(&self) -> Option<&'static str> {
+        if self.et_data[self.current_file_index].snapshot {
+            Some(SNAPSHOT_BINARY_MSG)
+        } else if self.current_is_sidecar() {
+            Some(SIDECAR_BINARY_MSG)
         } else {
-            self.scroll_offset.1 = self.scroll_offset.1.saturating_add(delta as u16);
+            None
         }
     }
 
-    /// Will return a 'key entry' for compare view
-    pub fn selected_entry(&self) -> Option<&TagEntry> {
-        if let Some(only_diff) = self.compare_data.mode {
-            let check_filter = |v: &Vec<Option<TagEntry>>| {
-                self.filter.is_empty()
-                    || v.iter()
-                        .any(|v| v.as_ref().is_some_and(|v| v.check_filter(&self.filter)))
-            };
+    /// Selected binary tag, if its preview wasn't extracted yet
+    fn missing_binary_preview(&self) -> Option<&TagEntry> {
+        if self.binary_unavailable().is_some() {
+            return None;
+        }
+        let entry = self
+            .selected_entry()
+            .filter(|e| e.binary_size_kb.is_some())?;
+        let cache_key = (self.current_file.clone(), entry.as_key());
+        (!self.binary_previews.contains_key(&cache_key)).then_some(entry)
+    }
 
-            let check_diff = |v: &Vec<Option<TagEntry>>| {
-                if !only_diff {
-                    true
-                } else {
-                    let first = &v[0];
-                    !v.iter().all(|entry| {
-                        (entry.is_none() && first.is_none())
-                            || entry
-                                .as_ref()
-                                .is_some_and(|e| first.as_ref().is_some_and(|f| e == f))
-                    })
-                }
-            };
+    /// Extracts leading bytes of selected binary tag right away, only once per tag
+    pub fn fetch_binary_preview(&mut self) {
+        let Some(entry) = self.missing_binary_preview() else {
+            return;
+        };
+        let cache_key = (self.current_file.clone(), entry.as_key());
+        let mut preview = entry
+            .get_binary(&self.exiftool, &self.current_file)
+            .unwrap_or_default();
+        preview.truncate(BINARY_PREVIEW_LEN);
+        self.binary_previews.insert(cache_key, preview);
+    }
 
-            self.compare_data
-                .data
-                .iter()
-                .filter(|ee| check_filter(&ee.1) && check_diff(&ee.1))
-                .map(|entry| entry.1[self.current_file_index].as_ref())
-                .nth(self.cursor)
-                .unwrap_or(None)
-        } else {
-            self.et_data[self.current_file_index]
-                .tag_entries
-                .iter()
-                .filter(|ee| self.filter.is_empty() || ee.check_filter(&self.filter))
-                .nth(self.cursor)
+    /// Takes preview extracted in background, or starts extracting one for selected binary tag.
+    /// Tells whether a preview has arrived
+    pub fn poll_binary_preview(&mut self) -> bool {
+        if let Some(job) = &self.preview_job {
+            if !job.handle.is_finished() {
+                return false;
+            }
+            let job = self.preview_job.take().expect("Job is checked above");
+            let mut preview = job.handle.join().unwrap_or_default();
+            preview.truncate(BINARY_PREVIEW_LEN);
+            self.binary_previews.insert(job.cache_key, preview);
+            return true;
+        }
+        let Some(entry) = self.missing_binary_preview().cloned() else {
+            return false;
+        };
+        let cache_key = (self.current_file.clone(), entry.as_key());
+        let exiftool = self.exiftool.clone();
+        let file = self.current_file.clone();
+        let handle =
+            std::thread::spawn(move || entry.get_binary(&exiftool, &file).unwrap_or_default());
+        self.preview_job = Some(PreviewJob { cache_key, handle });
+        false
+    }
+
+    /// Whether preview of `entry` is being extracted in background
+    pub fn binary_preview_pending(&self, entry: &TagEntry) -> bool {
+        self.preview_job
+            .as_ref()
+            .is_some_and(|job| job.cache_key == (self.current_file.clone(), entry.as_key()))
+    }
+
+    /// Extracts selected binary tag for hex viewer, tells whether it's ready to be shown
+    pub fn open_hex_view(&mut self) -> bool {
+        if let Some(msg) = self.binary_unavailable() {
+            self.log_msg = Some(Err(String::from(msg)));
+            return false;
+        }
+        let Some(entry) = self.selected_entry().filter(|e| e.binary_size_kb.is_some()) else {
+            self.log_msg = Some(Err(String::from(
+                "Selected entry does not contain any binary data!",
+            )));
+            return false;
+        };
+        match hexview::HexView::open(entry, &self.exiftool, &self.current_file) {
+            Ok(view) => {
+                self.hex_view = Some(view);
+                true
+            }
+            Err(e) => {
+                self.log_msg = Some(Err(e));
+                false
+            }
+        }
+    }
+
+    /// Selected value as hex: leading bytes of binary data, or integer value(s)
+    pub fn selected_value_hex(&mut self) -> Result<String, String> {
+        self.fetch_binary_preview();
+        let Some(entry) = self.selected_entry() else {
+            return Err(String::from("No tag selected!"));
+        };
+        if entry.binary_size_kb.is_some() {
+            if let Some(msg) = self.binary_unavailable() {
+                return Err(String::from(msg));
+            }
+            return match self.binary_preview(entry) {
+                Some(preview) if !preview.is_empty() => Ok(et_wrapper::bytes_hex(preview)),
+                _ => Err(String::from("Failed to extract binary data!")),
+            };
         }
+        entry
+            .integer_hex()
+            .ok_or_else(|| String::from("Value is neither binary data nor an integer!"))
+    }
+
+    pub fn selected_binary_type(&self) -> Option<(&'static str, &'static str)> {
+        self.selected_entry()
+            .and_then(|e| self.binary_preview(e))
+            .and_then(et_wrapper::detect_binary_type)
+    }
+
+    /// Empty preview means extraction has failed
+    pub fn binary_preview(&self, entry: &TagEntry) -> Option<&[u8]> {
+        self.binary_previews
+            .get(&(self.current_file.clone(), entry.as_key()))
+            .map(|b| b.as_slice())
     }
 
     pub fn try_save_binary(&mut self) -> Result<(), ()> {
@@ -209,8 +2491,8 @@ impl MainState {
             }
             path
         };
-        let entry = self.selected_entry().unwrap();
-        let binary = match entry.get_binary(&self.current_file) {
+        let entry = self.selected_entry().unwrap().clone();
+        let binary = match entry.get_binary(&self.exiftool, &self.current_file) {
             Ok(binary) => binary,
             Err(_) => {
                 return Err(());
@@ -220,17 +2502,285 @@ impl MainState {
         out.write_all(&binary)
             .expect("Failed to write binary data.");
         self.log_msg = Some(Ok(format!("Succesfully saved at {}", path.display())));
+        self.extractions.insert(
+            0,
+            Extraction {
+                source: self.current_file.clone(),
+                entry,
+                path,
+            },
+        );
         Ok(())
     }
 
+    /// Opens folder selected extraction was saved in
+    pub fn open_extraction_folder(&mut self) {
+        let Some(extraction) = self.extractions.get(self.extractions_cursor) else {
+            return;
+        };
+        let folder = extraction.path.parent().unwrap_or(Path::new("."));
+        if let Err(e) = open::that(folder) {
+            self.log_msg = Some(Err(format!("Failed to open {}: {e}", folder.display())));
+        }
+    }
+
+    /// Extracts selected binary data again to the same path, e.g. after source file has changed
+    pub fn repeat_extraction(&mut self) {
+        let Some(extraction) = self.extractions.get(self.extractions_cursor) else {
+            return;
+        };
+        let res = extraction
+            .entry
+            .get_binary(&self.exiftool, &extraction.source)
+            .map_err(|_| String::from("exiftool failed"))
+            .and_then(|binary| std::fs::write(&extraction.path, binary).map_err(|e| e.to_string()));
+        self.log_msg = Some(match res {
+            Ok(()) => Ok(format!(
+                "Succesfully saved at {}",
+                extraction.path.display()
+            )),
+            Err(e) => Err(format!("Failed to extract binary data again: {e}")),
+        });
+    }
+
+    /// Free path in Downloads, numbered if a file with this name already exists
+    fn unique_download_path(&self, stem: &str, ext: &str) -> Option<PathBuf> {
+        let dir = self.user_dirs.download_dir()?;
+        let mut path = dir.join(format!("{stem}.{ext}"));
+        let mut i = 1;
+        while path.exists() {
+            path = dir.join(format!("{stem} ({i}).{ext}"));
+            i += 1;
+        }
+        Some(path)
+    }
+
+    /// Exports to `path`, or to a new file in Downloads if it's not given
+    pub fn export_compare_xlsx(&mut self, path: Option<PathBuf>) {
+        let Some(path) = path.or_else(|| self.unique_download_path("compare", "xlsx")) else {
+            self.log_msg = Some(Err(String::from("Failed to obtain a downloads dir!")));
+            return;
+        };
+        let files = self
+            .compare_data
+            .files
+            .iter()
+            .map(|&i| self.et_data[i].file_name.as_path())
+            .collect::<Vec<_>>();
+        let rows = self
+            .visible_compare_rows()
+            .filter(|(k, _)| self.is_exported(k))
+            .collect::<Vec<_>>();
+        let marked_only = if self.marked.is_empty() {
+            String::new()
+        } else {
+            format!(" {} marked tags", rows.len())
+        };
+        let res = export::compare_to_xlsx(
+            &path,
+            &files,
+            &rows,
+            self.data_display_mode.short,
+            self.data_display_mode.numerical,
+        );
+        self.log_msg = Some(match res {
+            Ok(_) => Ok(format!(
+                "Succesfully exported{marked_only} at {}",
+                path.display()
+            )),
+            Err(e) => Err(format!("Failed to export spreadsheet: {e}")),
+        });
+    }
+
+    /// Exports shown tags of current file to `path`, or to `<file name>.tags.json` in Downloads
+    pub fn export_tags_json(&mut self, path: Option<PathBuf>) {
+        let file_name = self.et_data[self.current_file_index]
+            .file_name
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(path) =
+            path.or_else(|| self.unique_download_path(&format!("{file_name}.tags"), "json"))
+        else {
+            self.log_msg = Some(Err(String::from("Failed to obtain a downloads dir!")));
+            return;
+        };
+        let entries = self
+            .visible_entries()
+            .filter(|e| self.is_exported(e))
+            .collect::<Vec<_>>();
+        let res = serde_json::to_string_pretty(&export::tags_to_json(&entries))
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        self.log_msg = Some(match res {
+            Ok(()) => Ok(format!(
+                "Succesfully exported {} tags at {}",
+                entries.len(),
+                path.display()
+            )),
+            Err(e) => Err(format!("Failed to export tags: {e}")),
+        });
+    }
+
+    /// Exports shown compare rows to `path`, or to a new `compare.csv` in Downloads if it's not given
+    pub fn export_compare_csv(&mut self, path: Option<PathBuf>) {
+        let Some(path) = path.or_else(|| self.unique_download_path("compare", "csv")) else {
+            self.log_msg = Some(Err(String::from("Failed to obtain a downloads dir!")));
+            return;
+        };
+        let files = self
+            .compare_data
+            .files
+            .iter()
+            .map(|&i| self.et_data[i].file_name.as_path())
+            .collect::<Vec<_>>();
+        let rows = self
+            .visible_compare_rows()
+            .filter(|(k, _)| self.is_exported(k))
+            .collect::<Vec<_>>();
+        let csv = export::compare_to_csv(
+            &files,
+            &rows,
+            self.data_display_mode.short,
+            self.data_display_mode.numerical,
+        );
+        self.log_msg = Some(match std::fs::write(&path, csv) {
+            Ok(_) => Ok(format!(
+                "Succesfully exported {} tags at {}",
+                rows.len(),
+                path.display()
+            )),
+            Err(e) => Err(format!("Failed to export CSV: {e}")),
+        });
+    }
+
+    pub fn save_session(&mut self) {
+        let session = Session {
+            files: self
+                .et_data
+                .iter()
+                .filter(|et| !et.snapshot)
+                .map(|et| std::fs::canonicalize(&et.file_name).unwrap_or(et.file_name.clone()))
+                .collect(),
+            filter: self.filter.clone(),
+            compare_mode: self.compare_data.mode,
+            current_file_index: self.current_file_index,
+            cursor: self.cursor,
+            data_display_mode: self.data_display_mode.clone(),
+            sort_mode: self.sort_mode,
+        };
+        self.log_msg = Some(match session.save(&self.session_name) {
+            Ok(path) => Ok(format!("Session saved at {}", path.display())),
+            Err(e) => Err(format!("Failed to save session: {e}")),
+        });
+    }
+
+    /// Lists stored snapshots for snapshots popup
+    pub fn open_snapshots(&mut self) {
+        self.snapshot_names = Snapshot::list();
+        self.snapshots_cursor = 0;
+    }
+
+    /// Stores metadata of current file under `snapshot_name`, to compare the file against it later
+    pub fn save_snapshot(&mut self) {
+        let file = &self.et_data[self.current_file_index];
+        if file.snapshot {
+            self.log_msg = Some(Err(String::from("Current file is a snapshot already!")));
+            return;
+        }
+        let snapshot = Snapshot {
+            source: std::fs::canonicalize(&file.file_name).unwrap_or(file.file_name.clone()),
+            metadata: file.to_json(),
+        };
+        self.log_msg = Some(match snapshot.save(&self.snapshot_name) {
+            Ok(path) => Ok(format!("Snapshot saved at {}", path.display())),
+            Err(e) => Err(format!("Failed to save snapshot: {e}")),
+        });
+    }
+
+    /// Adds selected snapshot to loaded files as a pseudo-file and compares it with the others
+    pub fn load_selected_snapshot(&mut self) {
+        let Some(name) = self.snapshot_names.get(self.snapshots_cursor).cloned() else {
+            return;
+        };
+        let mut snapshot = match Snapshot::load(&name) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                self.log_msg = Some(Err(e.to_string()));
+                return;
+            }
+        };
+        let mut file = match et_wrapper::read_entry(&mut snapshot.metadata) {
+            Ok(file) => file,
+            Err(e) => {
+                self.log_msg = Some(Err(format!("Snapshot '{name}' is broken: {e}")));
+                return;
+            }
+        };
+        file.file_name = PathBuf::from(format!("{} [{name}]", snapshot.source.display()));
+        file.snapshot = true;
+        if self.et_data.iter().any(|et| et.file_name == file.file_name) {
+            self.log_msg = Some(Err(format!("Snapshot '{name}' is loaded already!")));
+            return;
+        }
+        self.et_data.push(file);
+        self.refresh_file_labels();
+        self.compare_data.files.push(self.et_data.len() - 1);
+        self.calculate_compare_data();
+        if self.compare_data.mode.is_off() {
+            self.compare_data.mode = CompareMode::All;
+            self.scroll_offset = (0, 0);
+            self.cursor = 0;
+        }
+        self.clamp_cursor();
+        self.log_msg = Some(Ok(format!("Snapshot '{name}' added to compare")));
+    }
+
+    pub fn delete_selected_snapshot(&mut self) {
+        let Some(name) = self.snapshot_names.get(self.snapshots_cursor).cloned() else {
+            return;
+        };
+        if let Err(e) = Snapshot::delete(&name) {
+            self.log_msg = Some(Err(format!("Failed to delete snapshot '{name}': {e}")));
+            return;
+        }
+        self.snapshot_names.retain(|n| n != &name);
+        self.snapshots_cursor = self
+            .snapshots_cursor
+            .min(self.snapshot_names.len().saturating_sub(1));
+    }
+
+    fn apply_session(&mut self, session: Session) {
+        self.filter = session.filter;
+        if self.is_multiple_files() {
+            self.compare_data.mode = session.compare_mode;
+        }
+        self.current_file_index = session.current_file_index.min(self.et_data.len() - 1);
+        self.current_file = self.et_data[self.current_file_index].file_name.clone();
+        self.cursor = session.cursor;
+        self.data_display_mode = session.data_display_mode;
+        self.sort_mode = session.sort_mode;
+        self.clamp_cursor();
+    }
+
     pub fn is_multiple_files(&self) -> bool {
         self.et_data.len() > 1
     }
 
+    /// Compares picked files, or all of them if fewer than two picked files are still loaded
     fn calculate_compare_data(&mut self) {
+        if self.compare_data.files.len() < 2
+            || self
+                .compare_data
+                .files
+                .iter()
+                .any(|&i| i >= self.et_data.len())
+        {
+            self.compare_data.files = (0..self.et_data.len()).collect();
+        }
         let mut keys = HashSet::new();
         let mut data = Vec::new();
-        for file_data in self.et_data.iter() {
+        for file_data in self.compare_data.files.iter().map(|&i| &self.et_data[i]) {
             let file_entries = file_data
                 .tag_entries
                 .iter()
@@ -269,6 +2819,13 @@ pub enum Screen {
     Main(MainInput),
     Help,
     MiltipleFilesStart,
+    Loading,
+    Start,
+    TagReport,
+    DiffSummary,
+    Exposures,
+    Keywords,
+    Gps,
 }
 
 impl Default for Screen {
@@ -277,7 +2834,7 @@ impl Default for Screen {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct DataDisplayMode {
     pub short: bool,
     pub numerical: bool,
@@ -290,36 +2847,318 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(image_path: PathBuf) -> std::io::Result<Self> {
+    pub fn new(image_path: PathBuf, exiftool: PathBuf) -> std::io::Result<Self> {
         Ok(Self {
             screen: Default::default(),
-            main_state: MainState::new(image_path)?,
+            main_state: MainState::new(image_path, exiftool)?,
             clipboard: copypasta::ClipboardContext::new()
                 .expect("Failed to obtain a clipboard context"),
         })
     }
 
-    pub fn new_multiple_files(input: Vec<PathBuf>) -> std::io::Result<Self> {
-        if input.iter().filter(|p| p.is_dir()).any(|p| {
-            std::fs::read_dir(p)
-                .unwrap()
-                .any(|p| p.unwrap().path().is_dir())
-        }) {
+    pub fn new_multiple_files(input: Vec<PathBuf>, exiftool: PathBuf) -> std::io::Result<Self> {
+        let (input, extracted_archives) = archive::extract_zips(input)?;
+        // Only folders with subfolders need a decision, flat ones read the same either way
+        let recursion_prompts = input
+            .iter()
+            .filter(|p| p.is_dir())
+            .filter(|p| {
+                std::fs::read_dir(p)
+                    .unwrap()
+                    .any(|p| p.unwrap().path().is_dir())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if !recursion_prompts.is_empty() {
+            let mut main_state = MainState::new_multiple_files(input, exiftool);
+            main_state.extracted_archives = extracted_archives;
+            main_state.recursion_prompts = recursion_prompts;
+            main_state.count_prompted_files();
             Ok(Self {
                 screen: Screen::MiltipleFilesStart,
-                main_state: MainState::new_multiple_files(input),
+                main_state,
                 clipboard: copypasta::ClipboardContext::new()
                     .expect("Failed to obtain a clipboard context"),
             })
         } else {
-            let mut main_state = MainState::new_multiple_files(input);
-            main_state.read_multiple_files(false)?;
+            let mut main_state = MainState::new_multiple_files(input, exiftool);
+            main_state.extracted_archives = extracted_archives;
+            main_state.start_loading()?;
             Ok(Self {
-                screen: Default::default(),
+                screen: Screen::Loading,
                 main_state,
                 clipboard: copypasta::ClipboardContext::new()
                     .expect("Failed to obtain a clipboard context"),
             })
         }
     }
+
+    /// Folders, zip archives and several paths go to multiple files mode
+    pub fn open(input: Vec<PathBuf>, exiftool: PathBuf) -> std::io::Result<Self> {
+        let mut app = if input.len() > 1 || input[0].is_dir() || archive::is_zip(&input[0]) {
+            Self::new_multiple_files(input.clone(), exiftool)
+        } else {
+            Self::new(input[0].clone(), exiftool)
+        }?;
+        app.main_state.storage.add_recents(&input);
+        // Failing to remember recents is not worth bothering user with
+        let _ = app.main_state.storage.save();
+        Ok(app)
+    }
+
+    /// Shown when launched without arguments, lets user pick a path to open
+    pub fn start_screen(exiftool: PathBuf) -> Self {
+        Self {
+            screen: Screen::Start,
+            main_state: MainState::new_multiple_files(Vec::new(), exiftool),
+            clipboard: copypasta::ClipboardContext::new()
+                .expect("Failed to obtain a clipboard context"),
+        }
+    }
+
+    /// Opens path chosen on start screen, typed one takes priority over selected recent
+    pub fn open_from_start(&mut self) {
+        let state = &mut self.main_state;
+        let path = if state.open_path_input.trim().is_empty() {
+            match state.storage.recents.get(state.recents_cursor) {
+                Some(path) => path.clone(),
+                None => return,
+            }
+        } else {
+            PathBuf::from(clean_dropped_path(&state.open_path_input))
+        };
+        if !path.exists() {
+            state.log_msg = Some(Err(format!("{} does not exist!", path.display())));
+            return;
+        }
+        match Self::open(vec![path], state.exiftool.clone()) {
+            Ok(app) => {
+                let light_theme = state.light_theme;
+                self.screen = app.screen;
+                self.main_state = app.main_state;
+                self.main_state.light_theme = light_theme;
+            }
+            Err(e) => state.log_msg = Some(Err(format!("Failed to open: {e}"))),
+        }
+    }
+
+    /// Switches to main screen once background loading is done, or back to start screen if it failed
+    pub fn poll_loading(&mut self) {
+        match self.main_state.poll_loading() {
+            Some(Ok(())) => self.screen = Screen::Main(Default::default()),
+            Some(Err(e)) => self.back_to_start(Err(format!("Failed to read files: {e}"))),
+            None => {}
+        }
+    }
+
+    /// Stops background loading and goes back to start screen
+    pub fn cancel_loading(&mut self) {
+        self.main_state.cancel_loading();
+        self.back_to_start(Ok(String::from("Loading cancelled")));
+    }
+
+    /// Resets to start screen, `msg` is shown in hints
+    pub fn back_to_start(&mut self, msg: Result<String, String>) {
+        let light_theme = self.main_state.light_theme;
+        self.screen = Screen::Start;
+        let exiftool = std::mem::take(&mut self.main_state.exiftool);
+        self.main_state = MainState::new_multiple_files(Vec::new(), exiftool);
+        self.main_state.light_theme = light_theme;
+        self.main_state.log_msg = Some(msg);
+    }
+
+    /// Pasted or dropped path replaces whatever was typed on start screen
+    pub fn paste_start_path(&mut self, text: &str) {
+        self.main_state.open_path_input = clean_dropped_path(text);
+    }
+
+    pub fn restore_session(name: &str, exiftool: PathBuf) -> std::io::Result<Self> {
+        let session = Session::load(name)?;
+        let mut main_state = MainState::new_multiple_files(session.files.clone(), exiftool);
+        main_state.read_multiple_files()?;
+        main_state.apply_session(session);
+        Ok(Self {
+            screen: Default::default(),
+            main_state,
+            clipboard: copypasta::ClipboardContext::new()
+                .expect("Failed to obtain a clipboard context"),
+        })
+    }
+}
+
+#[test]
+fn clean_dropped_path_test() {
+    assert_eq!(clean_dropped_path("  /tmp/a b.jpg\n"), "/tmp/a b.jpg");
+    assert_eq!(clean_dropped_path("'/tmp/a b.jpg' "), "/tmp/a b.jpg");
+    assert_eq!(clean_dropped_path("\"C:\\a b.jpg\""), "C:\\a b.jpg");
+    assert_eq!(clean_dropped_path("file:///tmp/a.jpg"), "/tmp/a.jpg");
+    assert_eq!(clean_dropped_path("'unbalanced"), "'unbalanced");
+}
+
+#[test]
+fn toggle_compare_mode_test() {
+    let mut state = MainState::new_multiple_files(Vec::new(), PathBuf::from("exiftool"));
+    state.et_data = vec![ExiftoolEntry {
+        file_name: PathBuf::from("a.jpg"),
+        tag_entries: Vec::new(),
+        snapshot: false,
+    }];
+    state.current_file = PathBuf::from("a.jpg");
+    state.refresh_file_labels();
+    // Single file has no compare data, toggling keeps it current
+    state.toggle_compare_mode();
+    assert!(state.compare_data.mode.is_on());
+    assert_eq!(state.current_file_index, 0);
+    state.toggle_compare_mode();
+    assert!(state.compare_data.mode.is_off());
+    assert_eq!(state.current_file, PathBuf::from("a.jpg"));
+}
+
+#[test]
+fn dedup_files_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.jpg");
+    let b = dir.path().join("b.jpg");
+    std::fs::write(&a, b"").unwrap();
+    std::fs::write(&b, b"").unwrap();
+    let file = |path: PathBuf| ExiftoolEntry {
+        file_name: path,
+        tag_entries: Vec::new(),
+        snapshot: false,
+    };
+
+    let mut et_data = vec![
+        file(a.clone()),
+        file(b.clone()),
+        file(a.clone()),
+        file(dir.path().join(".").join("a.jpg")),
+    ];
+    assert_eq!(dedup_files(&mut et_data), 2);
+    let names = et_data.iter().map(|et| &et.file_name).collect::<Vec<_>>();
+    assert_eq!(names, vec![&a, &b]);
+    assert_eq!(dedup_files(&mut et_data), 0);
+}
+
+#[test]
+fn sort_files_test() {
+    let file = |name: &str, date: Option<&str>| {
+        let tag_entries = date
+            .map(|date| et_wrapper::test_entry("DateTimeOriginal", "Exif::Main", date, None))
+            .into_iter()
+            .collect();
+        ExiftoolEntry {
+            file_name: PathBuf::from(name),
+            tag_entries,
+            snapshot: false,
+        }
+    };
+    let names = |et_data: &[ExiftoolEntry]| {
+        et_data
+            .iter()
+            .map(|et| et.file_name.display().to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut et_data = vec![
+        file("b/IMG_2.jpg", Some("2024:09:06 17:59:47")),
+        file("a/img_3.jpg", None),
+        file("c/IMG_1.jpg", Some("2024:09:06 09:00:00")),
+    ];
+
+    sort_files(&mut et_data, FileOrder::Input, "DateTimeOriginal");
+    assert_eq!(
+        names(&et_data),
+        ["b/IMG_2.jpg", "a/img_3.jpg", "c/IMG_1.jpg"]
+    );
+    sort_files(&mut et_data, FileOrder::Date, "DateTimeOriginal");
+    assert_eq!(
+        names(&et_data),
+        ["c/IMG_1.jpg", "b/IMG_2.jpg", "a/img_3.jpg"]
+    );
+    sort_files(&mut et_data, FileOrder::Name, "DateTimeOriginal");
+    assert_eq!(
+        names(&et_data),
+        ["c/IMG_1.jpg", "b/IMG_2.jpg", "a/img_3.jpg"]
+    );
+    et_data.reverse();
+    sort_files(&mut et_data, FileOrder::Name, "DateTimeOriginal");
+    assert_eq!(
+        names(&et_data),
+        ["c/IMG_1.jpg", "b/IMG_2.jpg", "a/img_3.jpg"]
+    );
+}
+
+#[test]
+fn batch_result_test() {
+    let outcome = |res: Result<&str, &str>| {
+        BatchResult::new(
+            PathBuf::from("a.jpg"),
+            res.map(str::to_owned).map_err(str::to_owned),
+        )
+        .outcome
+    };
+    assert_eq!(outcome(Ok("1 image files updated")), WriteOutcome::Updated);
+    assert_eq!(
+        outcome(Ok(
+            "Warning: nothing to do, 0 image files updated, 1 image files unchanged"
+        )),
+        WriteOutcome::Unchanged
+    );
+    assert_eq!(
+        outcome(Err("Error: File not found - a.jpg")),
+        WriteOutcome::Failed
+    );
+}
+
+#[test]
+fn sort_mode_test() {
+    let entry = |short_name: &str, name: &str, id: Option<u64>, table: &str| {
+        let mut entry = et_wrapper::test_entry(short_name, table, "", None);
+        entry.name = name.to_owned();
+        entry.id = id;
+        entry
+    };
+    let mut entries = vec![
+        entry("Model", "Camera Model Name", Some(272), "Exif::IFD0"),
+        entry("Creator", "Creator", None, "XMP::dc"),
+        entry("Make", "Make", Some(271), "Exif::IFD0"),
+        entry("ISO", "ISO", Some(34855), "Exif::ExifIFD"),
+    ];
+    let order = |entries: &[TagEntry]| {
+        entries
+            .iter()
+            .map(|e| e.short_name.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    entries.sort_by_cached_key(|e| SortMode::NameAsc.sort_key(e, true));
+    assert_eq!(order(&entries), "Creator ISO Make Model");
+    entries.sort_by_cached_key(|e| SortMode::NameAsc.sort_key(e, false));
+    assert_eq!(order(&entries), "Model Creator ISO Make");
+    entries.sort_by_cached_key(|e| SortMode::IdAsc.sort_key(e, true));
+    assert_eq!(order(&entries), "Make Model ISO Creator");
+    entries.sort_by_cached_key(|e| SortMode::GroupThenName.sort_key(e, true));
+    assert_eq!(order(&entries), "ISO Make Model Creator");
+    assert_eq!(SortMode::from_label("Group"), Some(SortMode::GroupThenName));
+    assert_eq!(SortMode::from_label("size"), None);
+}
+
+#[test]
+fn path_suffixes_test() {
+    let paths = [
+        Path::new("photos/2024/IMG_001.jpg"),
+        Path::new("photos/2025/IMG_001.jpg"),
+        Path::new("photos/2025/IMG_002.jpg"),
+    ];
+    assert_eq!(
+        path_suffixes(&paths),
+        ["2024/IMG_001.jpg", "2025/IMG_001.jpg", "IMG_002.jpg"]
+    );
+    let paths = [
+        Path::new("Фото/Лето/IMG_001.jpg"),
+        Path::new("Фото/Зима/IMG_001.jpg"),
+    ];
+    assert_eq!(
+        path_suffixes(&paths),
+        ["Лето/IMG_001.jpg", "Зима/IMG_001.jpg"]
+    );
 }
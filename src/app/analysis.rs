@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::et_wrapper::{parse_number, TagEntry};
+
+pub struct TagFrequency {
+    pub name: String,
+    pub short_name: String,
+    pub family: String,
+    pub files_with_tag: usize,
+    pub distinct_values: usize,
+}
+
+/// How much one file differs from the rest of compared files
+pub struct FileDiffSummary {
+    pub file_index: usize,
+    pub diffs_from_first: usize,
+    /// Average number of differing tags against every other file
+    pub mean_diffs: f32,
+}
+
+/// Columns of exposure table and short names of tags they are read from
+pub const EXPOSURE_COLUMNS: [(&str, &str); 4] = [
+    ("Shutter", "ExposureTime"),
+    ("Aperture", "FNumber"),
+    ("ISO", "ISO"),
+    ("Focal length", "FocalLength"),
+];
+
+/// Tags telling when a shot was taken, subseconds keep burst shots apart
+const CAPTURE_TIME_TAGS: [&str; 3] = ["SubSecDateTimeOriginal", "DateTimeOriginal", "CreateDate"];
+
+/// One shot of a sequence, values go in `EXPOSURE_COLUMNS` order
+pub struct ExposureRow {
+    pub file_index: usize,
+    pub taken: Option<String>,
+    pub values: Vec<Option<String>>,
+    /// Values as plain numbers, e.g. `0.004` for `1/250`
+    pub numbers: Vec<Option<f64>>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum TagFrequencySort {
+    #[default]
+    Presence,
+    DistinctValues,
+    Name,
+}
+
+impl TagFrequencySort {
+    pub fn next(self) -> Self {
+        match self {
+            TagFrequencySort::Presence => TagFrequencySort::DistinctValues,
+            TagFrequencySort::DistinctValues => TagFrequencySort::Name,
+            TagFrequencySort::Name => TagFrequencySort::Presence,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TagFrequencySort::Presence => "files with tag",
+            TagFrequencySort::DistinctValues => "distinct values",
+            TagFrequencySort::Name => "name",
+        }
+    }
+}
+
+/// Whether a compare row has different values, or the tag is missing in some files
+pub fn values_differ(values: &[Option<TagEntry>]) -> bool {
+    let first = &values[0];
+    !values.iter().all(|entry| {
+        (entry.is_none() && first.is_none())
+            || entry
+                .as_ref()
+                .is_some_and(|e| first.as_ref().is_some_and(|f| e == f))
+    })
+}
+
+/// Exposure settings of every file in capture order, files without capture time go last
+pub fn exposure_table(files: &[&[TagEntry]]) -> Vec<ExposureRow> {
+    let mut rows = files
+        .iter()
+        .enumerate()
+        .map(|(file_index, entries)| {
+            let find = |short_name: &str| entries.iter().find(|e| e.short_name == short_name);
+            let columns = EXPOSURE_COLUMNS.map(|(_, tag)| find(tag));
+            ExposureRow {
+                file_index,
+                taken: CAPTURE_TIME_TAGS
+                    .iter()
+                    .find_map(|tag| find(tag))
+                    .map(|e| e.val.to_string()),
+                values: columns
+                    .iter()
+                    .map(|e| e.map(|e| e.val.to_string()))
+                    .collect(),
+                numbers: columns
+                    .iter()
+                    .map(|e| e.and_then(|e| parse_number(&e.display_value(true))))
+                    .collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by_cached_key(|row| {
+        let taken = row.taken.as_deref().map(capture_time_key);
+        (taken.is_none(), taken)
+    });
+    rows
+}
+
+/// Capture time made comparable: date and time to the second, then nanoseconds from optional
+/// fractional seconds. Timezone suffix like `+02:00` or `Z` is ignored, times are compared as local
+fn capture_time_key(taken: &str) -> (String, u32) {
+    let Some(date_time) = taken.get(..19) else {
+        return (taken.to_owned(), 0);
+    };
+    let fraction = taken[19..]
+        .strip_prefix('.')
+        .map(|rest| {
+            let digits = rest
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .take(9)
+                .collect::<String>();
+            format!("{digits:0<9}").parse().unwrap_or(0)
+        })
+        .unwrap_or(0);
+    (date_time.to_owned(), fraction)
+}
+
+/// Whether a compare value is the one typed by user, in readable or numerical form.
+/// Missing tag never matches
+pub fn matches_expected(entry: Option<&TagEntry>, expected: &str) -> bool {
+    let expected = expected.trim();
+    entry.is_some_and(|e| {
+        e.val.to_string().trim() == expected
+            || e.num
+                .as_ref()
+                .is_some_and(|n| n.to_string().trim() == expected)
+    })
+}
+
+/// Number of differing tags for every pair of files, `[i][j]` is for files `i` and `j`
+pub fn pair_diff_counts(
+    data: &[(TagEntry, Vec<Option<TagEntry>>)],
+    num_files: usize,
+) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; num_files]; num_files];
+    for (_, values) in data {
+        for i in 0..num_files {
+            for j in i + 1..num_files {
+                if values[i] != values[j] {
+                    counts[i][j] += 1;
+                    counts[j][i] += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Per file diff counts, files differing the most from the others go first
+pub fn diff_summary(
+    data: &[(TagEntry, Vec<Option<TagEntry>>)],
+    num_files: usize,
+) -> Vec<FileDiffSummary> {
+    let counts = pair_diff_counts(data, num_files);
+    let mut summary = counts
+        .iter()
+        .enumerate()
+        .map(|(file_index, row)| FileDiffSummary {
+            file_index,
+            diffs_from_first: row[0],
+            mean_diffs: row.iter().sum::<usize>() as f32 / (num_files - 1).max(1) as f32,
+        })
+        .collect::<Vec<_>>();
+    summary.sort_by(|a, b| b.mean_diffs.total_cmp(&a.mean_diffs));
+    summary
+}
+
+/// How often each tag appears across files and how many values it takes
+pub fn tag_frequencies(data: &[(TagEntry, Vec<Option<TagEntry>>)]) -> Vec<TagFrequency> {
+    data.iter()
+        .map(|(key_entry, values)| TagFrequency {
+            name: key_entry.name.clone(),
+            short_name: key_entry.short_name.clone(),
+            family: key_entry.table_to_string(),
+            files_with_tag: values.iter().filter(|v| v.is_some()).count(),
+            distinct_values: values
+                .iter()
+                .flatten()
+                .map(|v| v.val.to_string())
+                .collect::<HashSet<_>>()
+                .len(),
+        })
+        .collect()
+}
+
+/// How many of the scored tags a file has with a non-empty value, as `(present, scored)`
+pub fn completeness(entries: &[TagEntry], scored_tags: &[String]) -> (usize, usize) {
+    let present = entries
+        .iter()
+        .filter(|e| !e.val.to_string().trim().is_empty())
+        .map(|e| e.short_name.as_str())
+        .collect::<HashSet<_>>();
+    let num_present = scored_tags
+        .iter()
+        .filter(|t| present.contains(t.as_str()))
+        .count();
+    (num_present, scored_tags.len())
+}
+
+pub fn sort_tag_frequencies(frequencies: &mut [TagFrequency], sort: TagFrequencySort) {
+    match sort {
+        TagFrequencySort::Presence => frequencies.sort_by(|a, b| {
+            b.files_with_tag
+                .cmp(&a.files_with_tag)
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        TagFrequencySort::DistinctValues => frequencies.sort_by(|a, b| {
+            b.distinct_values
+                .cmp(&a.distinct_values)
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        TagFrequencySort::Name => frequencies.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+}
+
+#[test]
+fn completeness_test() {
+    let entry = |short_name: &str, val: &str| test_entry(short_name, "Exif::Main", val, None);
+    let entries = vec![
+        entry("Make", "Canon"),
+        entry("Model", "Canon EOS R5"),
+        entry("Copyright", " "),
+        entry("ISO", "100"),
+    ];
+    let scored = ["Make", "Model", "Copyright", "GPSPosition"].map(String::from);
+    assert_eq!(completeness(&entries, &scored), (2, 4));
+    assert_eq!(completeness(&entries, &[]), (0, 0));
+}
+
+#[test]
+fn matches_expected_test() {
+    let entry = test_entry(
+        "Orientation",
+        "Exif::IFD0",
+        "Horizontal (normal)",
+        Some("1"),
+    );
+    assert!(matches_expected(Some(&entry), "Horizontal (normal)"));
+    assert!(matches_expected(Some(&entry), " 1 "));
+    assert!(!matches_expected(Some(&entry), "Rotate 90 CW"));
+    assert!(!matches_expected(None, ""));
+}
+
+#[test]
+fn exposure_table_test() {
+    let entry = |short_name: &str, val: &str, num: &str| {
+        test_entry(short_name, "Exif::ExifIFD", val, Some(num))
+    };
+    let first = vec![
+        entry(
+            "DateTimeOriginal",
+            "2024:09:06 10:00:01",
+            "2024:09:06 10:00:01",
+        ),
+        entry("ExposureTime", "1/250", "0.004"),
+        entry("ISO", "200", "200"),
+    ];
+    let untimed = vec![entry("FNumber", "8.0", "8")];
+    let second = vec![
+        entry(
+            "SubSecDateTimeOriginal",
+            "2024:09:06 10:00:00.50",
+            "2024:09:06 10:00:00.50",
+        ),
+        entry("ExposureTime", "1/125", "0.008"),
+    ];
+    let rows = exposure_table(&[&first, &untimed, &second]);
+    assert_eq!(
+        rows.iter().map(|r| r.file_index).collect::<Vec<_>>(),
+        [2, 0, 1]
+    );
+    assert_eq!(
+        rows[1].values,
+        [
+            Some(String::from("1/250")),
+            None,
+            Some(String::from("200")),
+            None
+        ]
+    );
+    assert_eq!(rows[0].numbers[0], Some(0.008));
+    assert_eq!(rows[2].numbers[1], Some(8.0));
+
+    // Same second with and without subseconds or timezone, text order would put `Z` last
+    let taken = |tag: &str, time: &str| vec![entry(tag, time, time)];
+    let rows = exposure_table(&[
+        &taken("SubSecDateTimeOriginal", "2024:09:06 10:00:00.50"),
+        &taken("DateTimeOriginal", "2024:09:06 10:00:00Z"),
+        &taken("SubSecDateTimeOriginal", "2024:09:06 10:00:00.25+02:00"),
+        &taken("CreateDate", "2024:09:06 09:59:59"),
+    ]);
+    assert_eq!(
+        rows.iter().map(|r| r.file_index).collect::<Vec<_>>(),
+        [3, 1, 2, 0]
+    );
+}
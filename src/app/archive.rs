@@ -0,0 +1,46 @@
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+pub fn is_zip(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Replaces every zip archive in `input` with a temp folder it was extracted to.
+/// Folders are removed once returned `TempDir`s are dropped
+pub fn extract_zips(input: Vec<PathBuf>) -> io::Result<(Vec<PathBuf>, Vec<TempDir>)> {
+    let mut temp_dirs = Vec::new();
+    let mut res = Vec::with_capacity(input.len());
+    for path in input {
+        if !is_zip(&path) {
+            res.push(path);
+            continue;
+        }
+        let temp_dir = tempfile::Builder::new()
+            .prefix("tool-exiftool-")
+            .tempdir()?;
+        // Folder named after archive keeps tab labels recognizable
+        let target = temp_dir
+            .path()
+            .join(path.file_stem().unwrap_or(path.as_os_str()));
+        ZipArchive::new(File::open(&path)?)
+            .and_then(|mut archive| archive.extract(&target))
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Failed to extract {}: {e}", path.display()),
+                )
+            })?;
+        res.push(target);
+        temp_dirs.push(temp_dir);
+    }
+    Ok((res, temp_dirs))
+}
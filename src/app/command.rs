@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use super::SortMode;
+
+/// Command names, as typed after `:`
+pub const COMMANDS: [&str; 6] = ["goto", "filter", "sort", "export", "write", "quit"];
+
+/// Tokens `filter` understands besides plain text, see `TagEntry::check_filter`
+const FILTER_TOKENS: [&str; 3] = [":array", ":binary", ":empty"];
+
+/// Action typed in command line, e.g. `:goto 42`
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    /// 1-based index of a shown entry
+    Goto(usize),
+    Filter(String),
+    Sort(SortMode),
+    Export {
+        format: String,
+        path: Option<PathBuf>,
+    },
+    /// Empty value removes the tag
+    Write {
+        tag: String,
+        value: String,
+    },
+    Quit,
+}
+
+/// Parses command line, a bare number is a shortcut for `goto`
+pub fn parse(input: &str) -> Result<Action, String> {
+    let input = input.trim();
+    if let Ok(index) = input.parse() {
+        return Ok(Action::Goto(index));
+    }
+    let (name, args) = input.split_once(' ').unwrap_or((input, ""));
+    let args = args.trim();
+    match name {
+        "goto" | "g" => args
+            .parse()
+            .map(Action::Goto)
+            .map_err(|_| String::from("Usage: goto <index>")),
+        "filter" | "f" => Ok(Action::Filter(args.to_owned())),
+        "sort" | "s" => SortMode::from_label(args)
+            .map(Action::Sort)
+            .ok_or_else(|| String::from("Usage: sort none|name|id|group")),
+        "export" | "e" => {
+            let (format, path) = args.split_once(' ').unwrap_or((args, ""));
+            if format.is_empty() {
+                return Err(String::from("Usage: export <format> [path]"));
+            }
+            Ok(Action::Export {
+                format: format.to_lowercase(),
+                path: (!path.trim().is_empty()).then(|| PathBuf::from(path.trim())),
+            })
+        }
+        "write" | "w" => {
+            let (tag, value) = args.split_once(' ').unwrap_or((args, ""));
+            if tag.is_empty() {
+                return Err(String::from("Usage: write <tag> [value]"));
+            }
+            Ok(Action::Write {
+                tag: tag.to_owned(),
+                value: value.trim().to_owned(),
+            })
+        }
+        "quit" | "q" => Ok(Action::Quit),
+        "" => Err(String::from("Type a command, e.g. `goto 42`")),
+        _ => Err(format!(
+            "Unknown command '{name}', available: {}",
+            COMMANDS.join(", ")
+        )),
+    }
+}
+
+/// Whole command lines the last word of `input` can be completed to: command names for the first word,
+/// then tag names for `filter`/`write`, families inside `<<...>>` of a filter and orders for `sort`
+pub fn completions(input: &str, tags: &[String], families: &[String]) -> Vec<String> {
+    let (head, word) = match input.rfind(' ') {
+        Some(pos) => input.split_at(pos + 1),
+        None => ("", input),
+    };
+    let command = input.split(' ').next().unwrap_or_default();
+    let candidates = if head.is_empty() {
+        COMMANDS.iter().map(|c| c.to_string()).collect()
+    } else if word.starts_with("<<") && matches!(command, "filter" | "f") {
+        families.iter().map(|f| format!("<<{f}>>")).collect()
+    } else if matches!(command, "filter" | "f") {
+        FILTER_TOKENS
+            .iter()
+            .map(|t| t.to_string())
+            .chain(tags.iter().cloned())
+            .collect()
+    } else if matches!(command, "write" | "w") && head.trim() == command {
+        tags.to_vec()
+    } else if matches!(command, "sort" | "s") && head.trim() == command {
+        ["none", "name", "id", "group"].map(String::from).to_vec()
+    } else {
+        Vec::new()
+    };
+    let word = word.to_lowercase();
+    let mut completions = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&word))
+        .map(|c| format!("{head}{c}"))
+        .collect::<Vec<_>>();
+    completions.sort();
+    completions.dedup();
+    completions
+}
+
+#[test]
+fn completions_test() {
+    let tags = ["Make", "Model", "ISO", "LensModel"].map(String::from);
+    let families = ["Exif", "XMP", "XMP::dc"].map(String::from);
+
+    assert_eq!(completions("f", &tags, &families), vec!["filter"]);
+    assert_eq!(completions("", &tags, &families).len(), COMMANDS.len());
+    assert_eq!(
+        completions("write m", &tags, &families),
+        vec!["write Make", "write Model"]
+    );
+    assert!(completions("write Make m", &tags, &families).is_empty());
+    assert_eq!(
+        completions("filter <<x", &tags, &families),
+        vec!["filter <<XMP::dc>>", "filter <<XMP>>"]
+    );
+    assert_eq!(
+        completions("filter :a", &tags, &families),
+        vec!["filter :array"]
+    );
+    assert_eq!(
+        completions("filter :array le", &tags, &families),
+        vec!["filter :array LensModel"]
+    );
+    assert!(completions("goto 4", &tags, &families).is_empty());
+    assert_eq!(
+        completions("sort n", &tags, &families),
+        vec!["sort name", "sort none"]
+    );
+}
+
+#[test]
+fn parse_test() {
+    assert_eq!(parse("42"), Ok(Action::Goto(42)));
+    assert_eq!(parse(" goto 7 "), Ok(Action::Goto(7)));
+    assert!(parse("goto seven").is_err());
+    assert_eq!(
+        parse("filter <<xmp>> :array"),
+        Ok(Action::Filter(String::from("<<xmp>> :array")))
+    );
+    assert_eq!(parse("filter"), Ok(Action::Filter(String::new())));
+    assert_eq!(parse("sort Name"), Ok(Action::Sort(SortMode::NameAsc)));
+    assert!(parse("sort size").is_err());
+    assert_eq!(
+        parse("export XLSX /tmp/my compare.xlsx"),
+        Ok(Action::Export {
+            format: String::from("xlsx"),
+            path: Some(PathBuf::from("/tmp/my compare.xlsx")),
+        })
+    );
+    assert_eq!(
+        parse("export xlsx"),
+        Ok(Action::Export {
+            format: String::from("xlsx"),
+            path: None,
+        })
+    );
+    assert!(parse("export").is_err());
+    assert_eq!(
+        parse("write Artist John Doe"),
+        Ok(Action::Write {
+            tag: String::from("Artist"),
+            value: String::from("John Doe"),
+        })
+    );
+    assert_eq!(
+        parse("write Artist"),
+        Ok(Action::Write {
+            tag: String::from("Artist"),
+            value: String::new(),
+        })
+    );
+    assert_eq!(parse("q"), Ok(Action::Quit));
+    assert!(parse("frobnicate").is_err());
+    assert!(parse("").is_err());
+}
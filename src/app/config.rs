@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml_edit::{Array, DocumentMut};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::SortMode;
+
+/// Which colors to use, `Auto` asks terminal for its background color
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeSetting {
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+/// Order of files (tabs) in multiple files mode
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOrder {
+    /// As given on command line or found in folders
+    #[default]
+    Input,
+    Name,
+    /// By `file_order_date_tag`, files without it go last
+    Date,
+}
+
+/// User settings, read from `config.toml` in user's config dir
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Values longer than this many details pane widths are cut
+    pub details_value_max_lines: usize,
+    /// How many details pane widths of a cut value are still shown
+    pub details_value_preview_lines: usize,
+    /// Alternate background of odd rows in tag list
+    pub zebra_stripes: bool,
+    /// Pad tag names with dots up to value column
+    pub leader_dots: bool,
+    /// Terminals narrower than this get single column `tag: value` list
+    pub compact_width: u16,
+    /// Force single column list on or off regardless of `compact_width`
+    pub compact_layout: Option<bool>,
+    /// Show short tag names, e.g. `FNumber` instead of `F Number`
+    pub short_names: bool,
+    /// Show numerical values, e.g. `2.8` instead of `f/2.8`
+    pub numerical_values: bool,
+    /// Place details pane under tag list instead of beside it
+    pub details_at_bottom: bool,
+    /// Details pane shows tag ID, family, raw values and analysis sections, not only name and value
+    pub details_verbose: bool,
+    /// Plain filter also matches tag family, not only names and values
+    pub filter_matches_family: bool,
+    /// Mark lines clipped on the left with `...` while scrolled horizontally
+    pub scroll_ellipsis: bool,
+    /// Prefix tag names with exiftool's instance qualifier, e.g. `Copy1:Make`
+    pub show_instance: bool,
+    /// Disable every action which modifies files
+    pub read_only: bool,
+    /// Show elements of array values on separate rows
+    pub vertical_arrays: bool,
+    /// Short names of tags shown in essentials quick view
+    pub essential_tags: Vec<String>,
+    pub theme: ThemeSetting,
+    /// Read XMP structures as nested values (exiftool's `-struct`) instead of flattened tags
+    pub structured_xmp: bool,
+    /// Lens names by raw `LensModel`/`LensID` value, extending and overriding bundled ones
+    pub lens_names: HashMap<String, String>,
+    /// At most this many file columns are shown at once in compare mode, the rest are paged
+    pub compare_max_columns: usize,
+    /// Short names of tags a file is expected to have, completeness score counts them
+    pub completeness_tags: Vec<String>,
+    /// Look up nearest place of GPS position in GPS panel, needs exiftool 12.78 or newer
+    pub geolocation: bool,
+    /// How often time-based content is updated without any input, in milliseconds
+    pub tick_interval_ms: u64,
+    pub file_order: FileOrder,
+    /// Short name of tag files are ordered by with `file_order = "date"`
+    pub file_order_date_tag: String,
+    /// Leave exiftool's own warnings and errors out of tag list, header still counts them
+    pub hide_warnings: bool,
+    /// Color tag names by family, each family always gets the same color
+    pub family_colors: bool,
+    /// Colors of specific families, by name, index or `#rrggbb`, taking place of derived ones
+    pub family_color_overrides: HashMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            details_value_max_lines: 5,
+            details_value_preview_lines: 3,
+            zebra_stripes: false,
+            leader_dots: false,
+            compact_width: 60,
+            compact_layout: None,
+            short_names: false,
+            numerical_values: false,
+            details_at_bottom: false,
+            details_verbose: true,
+            filter_matches_family: true,
+            scroll_ellipsis: true,
+            show_instance: false,
+            vertical_arrays: false,
+            read_only: false,
+            essential_tags: [
+                "Make",
+                "Model",
+                "LensModel",
+                "DateTimeOriginal",
+                "ExposureTime",
+                "FNumber",
+                "ISO",
+                "FocalLength",
+                "ImageSize",
+                "GPSPosition",
+            ]
+            .map(String::from)
+            .to_vec(),
+            theme: ThemeSetting::Auto,
+            structured_xmp: false,
+            lens_names: HashMap::new(),
+            compare_max_columns: 4,
+            completeness_tags: [
+                "Make",
+                "Model",
+                "LensModel",
+                "DateTimeOriginal",
+                "GPSPosition",
+                "Artist",
+                "Copyright",
+            ]
+            .map(String::from)
+            .to_vec(),
+            geolocation: false,
+            tick_interval_ms: 1000,
+            file_order: FileOrder::Input,
+            file_order_date_tag: String::from("DateTimeOriginal"),
+            hide_warnings: true,
+            family_colors: false,
+            family_color_overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Per-folder config file name, its settings apply to files opened from that folder
+pub const DIR_CONFIG_FILE: &str = ".tool-exiftool.toml";
+
+/// Settings for files of one folder, read from `.tool-exiftool.toml` inside of it,
+/// they take priority over global config
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct DirConfig {
+    /// Filter set right after files of the folder are opened
+    pub filter: Option<String>,
+    /// Tag order set right after files of the folder are opened, e.g. `name` or `group`
+    pub sort: Option<SortMode>,
+}
+
+impl DirConfig {
+    /// Missing or broken folder config is ignored
+    pub fn load(dir: &Path) -> Option<Self> {
+        let data = fs::read_to_string(dir.join(DIR_CONFIG_FILE)).ok()?;
+        toml::from_str(&data).ok()
+    }
+}
+
+pub fn config_file_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "tool-exiftool").map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// Display toggles which can be changed while running, they are written back to config on quit
+#[derive(Clone, PartialEq, Eq)]
+pub struct DisplayPrefs {
+    pub zebra_stripes: bool,
+    pub compact_layout: Option<bool>,
+    pub short_names: bool,
+    pub numerical_values: bool,
+    pub details_at_bottom: bool,
+    pub details_verbose: bool,
+    pub filter_matches_family: bool,
+    pub scroll_ellipsis: bool,
+    pub show_instance: bool,
+    pub vertical_arrays: bool,
+}
+
+impl Config {
+    pub fn display_prefs(&self) -> DisplayPrefs {
+        DisplayPrefs {
+            zebra_stripes: self.zebra_stripes,
+            compact_layout: self.compact_layout,
+            short_names: self.short_names,
+            numerical_values: self.numerical_values,
+            details_at_bottom: self.details_at_bottom,
+            details_verbose: self.details_verbose,
+            filter_matches_family: self.filter_matches_family,
+            scroll_ellipsis: self.scroll_ellipsis,
+            show_instance: self.show_instance,
+            vertical_arrays: self.vertical_arrays,
+        }
+    }
+
+    /// Missing config file is not an error, default config is used then
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = config_file_path() else {
+            return Ok(Default::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(data) => toml::from_str(&data)
+                .map_err(|e| format!("Failed to parse {}: {}", path.display(), e.message())),
+            Err(_) => Ok(Default::default()),
+        }
+    }
+}
+
+/// Applies `edit` to config file, keeping the rest of it (comments included) untouched
+fn edit_config_file(edit: impl FnOnce(&mut DocumentMut)) -> Result<(), String> {
+    let path = config_file_path().ok_or("Failed to locate config dir!")?;
+    let mut doc = fs::read_to_string(&path)
+        .unwrap_or_default()
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+    edit(&mut doc);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, doc.to_string())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Writes essentials list to config file
+pub fn save_essential_tags(tags: &[String]) -> Result<(), String> {
+    edit_config_file(|doc| {
+        doc["essential_tags"] = toml_edit::value(tags.iter().collect::<Array>());
+    })
+}
+
+/// Writes display toggles to config file
+pub fn save_display_prefs(prefs: &DisplayPrefs) -> Result<(), String> {
+    edit_config_file(|doc| {
+        doc["zebra_stripes"] = toml_edit::value(prefs.zebra_stripes);
+        match prefs.compact_layout {
+            Some(compact) => doc["compact_layout"] = toml_edit::value(compact),
+            None => {
+                doc.remove("compact_layout");
+            }
+        }
+        doc["short_names"] = toml_edit::value(prefs.short_names);
+        doc["numerical_values"] = toml_edit::value(prefs.numerical_values);
+        doc["details_at_bottom"] = toml_edit::value(prefs.details_at_bottom);
+        doc["details_verbose"] = toml_edit::value(prefs.details_verbose);
+        doc["filter_matches_family"] = toml_edit::value(prefs.filter_matches_family);
+        doc["scroll_ellipsis"] = toml_edit::value(prefs.scroll_ellipsis);
+        doc["show_instance"] = toml_edit::value(prefs.show_instance);
+        doc["vertical_arrays"] = toml_edit::value(prefs.vertical_arrays);
+    })
+}
+
+#[test]
+fn dir_config_test() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(DirConfig::load(dir.path()).is_none());
+
+    fs::write(
+        dir.path().join(DIR_CONFIG_FILE),
+        "filter = \"<<iptc>> copyright\"\nsort = \"group\"\n",
+    )
+    .unwrap();
+    let config = DirConfig::load(dir.path()).unwrap();
+    assert_eq!(config.filter.as_deref(), Some("<<iptc>> copyright"));
+    assert_eq!(config.sort, Some(SortMode::GroupThenName));
+
+    fs::write(dir.path().join(DIR_CONFIG_FILE), "filter = [").unwrap();
+    assert!(DirConfig::load(dir.path()).is_none());
+}
@@ -1,51 +1,164 @@
 use std::{
+    fmt,
+    io::Read,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Child, Command, Stdio},
+    sync::LazyLock,
+    thread::JoinHandle,
 };
 
-use serde::{Deserialize, Deserializer};
-use serde_json::Value;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 pub enum EtVal {
     String(String),
     Array(Vec<Value>),
+    /// XMP structure, only given by exiftool with `-struct`
+    Object(Map<String, Value>),
 }
 
-impl EtVal {
-    fn check_filter(&self, filter: &str) -> bool {
-        match &self {
-            EtVal::String(s) => s.to_lowercase().contains(&filter),
-            EtVal::Array(vec) => {
-                let mut res = false;
-                for v in vec {
-                    if v.to_string().contains(&filter) {
-                        res = true;
-                        break;
-                    }
-                }
-                res
+/// Nested values in exiftool's own notation, e.g. `{Name=John,Roles=[a,b]}`
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(vec) => format!(
+            "[{}]",
+            vec.iter()
+                .map(value_to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        Value::Object(map) => format!(
+            "{{{}}}",
+            map.iter()
+                .map(|(k, v)| format!("{k}={}", value_to_string(v)))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        v => v.to_string(),
+    }
+}
+
+/// Indented lines of a nested value, each field or array element on its own line
+fn push_tree_lines(value: &Value, indent: usize, lines: &mut Vec<String>) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Array(vec) => {
+            for (i, v) in vec.iter().enumerate() {
+                push_tree_line(&format!("{pad}[{i}]"), v, indent, lines);
             }
         }
+        Value::Object(map) => {
+            for (k, v) in map {
+                push_tree_line(&format!("{pad}{k}"), v, indent, lines);
+            }
+        }
+        v => lines.push(format!("{pad}{}", value_to_string(v))),
+    }
+}
+
+fn push_tree_line(label: &str, value: &Value, indent: usize, lines: &mut Vec<String>) {
+    if matches!(value, Value::Array(_) | Value::Object(_)) {
+        lines.push(format!("{label}:"));
+        push_tree_lines(value, indent + 1, lines);
+    } else {
+        lines.push(format!("{label}: {}", value_to_string(value)));
     }
+}
 
-    pub fn to_string(&self) -> String {
+impl EtVal {
+    /// Value as exiftool gave it, arrays keep their json form
+    pub fn raw(&self) -> String {
         match self {
             EtVal::String(s) => s.clone(),
+            EtVal::Array(vec) => Value::Array(vec.clone()).to_string(),
+            EtVal::Object(map) => Value::Object(map.clone()).to_string(),
+        }
+    }
+
+    /// Array elements as shown to user, strings without json quotes
+    pub fn elements(&self) -> Vec<String> {
+        match self {
+            EtVal::String(s) => vec![s.clone()],
+            EtVal::Array(vec) => vec.iter().map(value_to_string).collect(),
+            EtVal::Object(map) => vec![value_to_string(&Value::Object(map.clone()))],
+        }
+    }
+
+    /// Whether value holds XMP structures, which are better shown as a tree
+    pub fn is_structured(&self) -> bool {
+        match self {
+            EtVal::String(_) => false,
             EtVal::Array(vec) => vec
                 .iter()
-                .map(|v| v.to_string())
-                .collect::<Vec<_>>()
-                .join(" "),
+                .any(|v| matches!(v, Value::Array(_) | Value::Object(_))),
+            EtVal::Object(_) => true,
+        }
+    }
+
+    /// Hierarchical view of structured value for details pane
+    pub fn tree_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        match self {
+            EtVal::String(s) => lines.push(s.clone()),
+            EtVal::Array(vec) => push_tree_lines(&Value::Array(vec.clone()), 0, &mut lines),
+            EtVal::Object(map) => push_tree_lines(&Value::Object(map.clone()), 0, &mut lines),
         }
+        lines
+    }
+
+    fn check_filter(&self, filter: &str) -> bool {
+        self.elements()
+            .iter()
+            .any(|v| v.to_lowercase().contains(filter))
     }
 }
 
-#[derive(Debug)]
+impl fmt::Display for EtVal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.elements().join(" "))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ExiftoolEntry {
     pub file_name: PathBuf,
     pub tag_entries: Vec<TagEntry>,
+    /// Metadata was restored from a stored snapshot, there is no such file on disk
+    pub snapshot: bool,
+}
+
+impl ExiftoolEntry {
+    /// Metadata back in the form exiftool prints it, so that `read_entry` can restore it
+    pub fn to_json(&self) -> Value {
+        let mut res = Map::new();
+        res.insert(
+            String::from("SourceFile"),
+            Value::String(self.file_name.display().to_string()),
+        );
+        for entry in &self.tag_entries {
+            let key = if entry.instance.is_empty() {
+                entry.short_name.clone()
+            } else {
+                format!("{}:{}", entry.instance, entry.short_name)
+            };
+            res.insert(
+                key,
+                serde_json::json!({
+                    "desc": entry.name,
+                    "id": entry.id,
+                    "table": entry.table_to_string(),
+                    "val": entry.val,
+                    "num": entry.num,
+                    "index": entry.index,
+                }),
+            );
+        }
+        Value::Object(res)
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -67,7 +180,7 @@ pub struct TagEntry {
     pub index: Option<u64>,
 }
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub struct TagEntryKey {
     pub short_name: String,
     pub table: (String, String),
@@ -87,7 +200,7 @@ where
     D: Deserializer<'de>,
 {
     let deserialized = u64::deserialize(deserializer);
-    Ok(deserialized.map_or(None, |num| Some(num)))
+    Ok(deserialized.ok())
 }
 
 fn deserialize_table<'de, D>(deserializer: D) -> Result<(String, String), D::Error>
@@ -118,9 +231,50 @@ impl PartialEq for TagEntry {
 
 // impl Eq for TagEntry {}
 
+/// Tag as exiftool gives it, named `short_name` in both short and detailed mode
+#[cfg(test)]
+pub fn test_entry(
+    short_name: &str,
+    table: &str,
+    val: impl Into<Value>,
+    num: Option<&str>,
+) -> TagEntry {
+    let mut entry: TagEntry = serde_json::from_value(serde_json::json!({
+        "desc": short_name,
+        "id": null,
+        "table": table,
+        "val": val.into(),
+        "num": num,
+    }))
+    .unwrap();
+    entry.short_name = short_name.to_owned();
+    entry
+}
+
 impl TagEntry {
-    pub fn check_filter(&self, filter: &str) -> bool {
+    /// With `match_family` plain filter also matches everything in a family, e.g. `xmp`
+    /// Tokens `:array`, `:binary` and `:empty` match by kind of value and combine with the rest
+    pub fn check_filter(&self, filter: &str, match_family: bool) -> bool {
         let filter = filter.to_lowercase();
+        let mut text_terms = Vec::new();
+        for term in filter.split(' ') {
+            let matches = match term {
+                ":array" => matches!(self.val, EtVal::Array(_)),
+                ":binary" => self.binary_size_kb.is_some(),
+                ":empty" => self.val.to_string().trim().is_empty(),
+                _ => match numeric_term(term) {
+                    Some(comparison) => self.check_numeric_term(comparison),
+                    None => {
+                        text_terms.push(term);
+                        true
+                    }
+                },
+            };
+            if !matches {
+                return false;
+            }
+        }
+        let filter = text_terms.join(" ");
         if filter.starts_with("<<") && filter.ends_with(">>") {
             self.table_to_string()
                 .to_lowercase()
@@ -133,9 +287,24 @@ impl TagEntry {
                     .num
                     .as_ref()
                     .is_some_and(|num| num.check_filter(&filter))
+                || (match_family && self.table_to_string().to_lowercase().contains(&filter))
         }
     }
 
+    /// Whether this is the tag named in a term like `iso>1600`, with its numerical value in range
+    fn check_numeric_term(&self, (tag, operator, threshold): (&str, &str, f64)) -> bool {
+        let Some(value) = parse_number(&self.display_value(true)) else {
+            return false;
+        };
+        self.short_name.to_lowercase() == tag
+            && match operator {
+                ">" => value > threshold,
+                "<" => value < threshold,
+                ">=" => value >= threshold,
+                _ => value <= threshold,
+            }
+    }
+
     pub fn open_web_page(&self) {
         if self.table.0 == "Exif" {
             let _ = open::that("https://exiftool.org/TagNames/EXIF.html");
@@ -147,6 +316,74 @@ impl TagEntry {
         ));
     }
 
+    /// Whether exiftool reported a problem with this tag, e.g. `Warning` or `Error`
+    pub fn is_warning_or_error(&self) -> bool {
+        let short_name = self.short_name.to_lowercase();
+        short_name.contains("warning") || short_name.contains("error")
+    }
+
+    /// Warning or error exiftool reports about the file, not a metadata tag of it
+    pub fn is_exiftool_message(&self) -> bool {
+        matches!(self.short_name.as_str(), "Warning" | "Error")
+    }
+
+    /// Value as shown in tag list, respecting numerical display mode
+    pub fn display_value(&self, numerical: bool) -> String {
+        self.display_val(numerical).to_string()
+    }
+
+    pub fn display_val(&self, numerical: bool) -> &EtVal {
+        match &self.num {
+            Some(num) if numerical => num,
+            _ => &self.val,
+        }
+    }
+
+    /// Numerical value as hex, e.g. `0x1A`, space separated for arrays.
+    /// None if some of the elements is not an integer
+    pub fn integer_hex(&self) -> Option<String> {
+        let elements = self.display_val(true).elements();
+        if elements.is_empty() {
+            return None;
+        }
+        elements
+            .iter()
+            .map(|e| {
+                let e = e.trim();
+                match e.parse::<u64>() {
+                    Ok(n) => Some(format!("{n:#X}")),
+                    Err(_) => e
+                        .parse::<i64>()
+                        .ok()
+                        .map(|n| format!("-{:#X}", n.unsigned_abs())),
+                }
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|hex| hex.join(" "))
+    }
+
+    /// Tag as exiftool takes it on command line, e.g. `XMP:Creator`
+    pub fn tag_arg(&self) -> String {
+        format!("{}:{}", self.table.0, self.short_name)
+    }
+
+    /// Exiftool command to read (`r`), write current value back (`w`) or delete (`d`) this tag in `file`
+    pub fn command_template(&self, kind: char, file: &Path) -> Option<String> {
+        let tag = self.tag_arg();
+        let args = match kind {
+            'r' => vec![
+                String::from("-a"),
+                String::from("-G1"),
+                String::from("-s"),
+                format!("-{tag}"),
+            ],
+            'w' => vec![format!("-{tag}={}", self.val.to_string().trim())],
+            'd' => vec![format!("-{tag}=")],
+            _ => return None,
+        };
+        Some(write_command_line(&args, &[file.to_path_buf()]))
+    }
+
     pub fn table_to_string(&self) -> String {
         if self.table.1.is_empty() {
             self.table.0.clone()
@@ -155,8 +392,56 @@ impl TagEntry {
         }
     }
 
-    pub fn to_string(&self) -> String {
-        let mut res = format!(
+    pub fn get_binary(&self, exiftool: &Path, image_path: &Path) -> Result<Vec<u8>, ()> {
+        if self.binary_size_kb.is_none() {
+            return Err(());
+        }
+
+        Ok(Command::new(exiftool)
+            .arg(format!("-{}", self.short_name))
+            .arg("-b")
+            .arg("--")
+            .arg(image_path)
+            .output()
+            .map_err(|_| ())?
+            .stdout)
+    }
+
+    /// Streams binary data straight into `out`, so it is never held in memory as a whole
+    pub fn write_binary_to(
+        &self,
+        exiftool: &Path,
+        image_path: &Path,
+        out: std::fs::File,
+    ) -> Result<(), ()> {
+        if self.binary_size_kb.is_none() {
+            return Err(());
+        }
+
+        let status = Command::new(exiftool)
+            .arg(format!("-{}", self.short_name))
+            .arg("-b")
+            .arg("--")
+            .arg(image_path)
+            .stdout(out)
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|_| ())?;
+        status.success().then_some(()).ok_or(())
+    }
+
+    pub fn as_key(&self) -> TagEntryKey {
+        TagEntryKey {
+            short_name: self.short_name.clone(),
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl fmt::Display for TagEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "Name: {}
 Short name: {}
 Tag ID: {}
@@ -171,41 +456,15 @@ Tag numerical value: {}",
                 "Unknown".into()
             },
             self.table_to_string(),
-            self.val.to_string(),
-            if let Some(num) = &self.num {
-                num.to_string()
-            } else {
-                self.val.to_string()
-            }
-        );
+            self.val,
+            self.num.as_ref().unwrap_or(&self.val)
+        )?;
 
         if let Some(index) = self.index {
-            res.push_str("\nTag index: ");
-            res += &index.to_string();
+            write!(f, "\nTag index: {index}")?;
         }
 
-        res
-    }
-
-    pub fn get_binary(&self, image_path: &Path) -> Result<Vec<u8>, ()> {
-        if let None = self.binary_size_kb {
-            return Err(());
-        }
-
-        Ok(Command::new("exiftool")
-            .arg(image_path)
-            .arg(&format!("-{}", self.short_name))
-            .arg("-b")
-            .output()
-            .map_err(|_| ())?
-            .stdout)
-    }
-
-    pub fn as_key(&self) -> TagEntryKey {
-        TagEntryKey {
-            short_name: self.short_name.clone(),
-            table: self.table.clone(),
-        }
+        Ok(())
     }
 }
 
@@ -266,12 +525,135 @@ Tag numerical value: {}",
 //     }
 // }
 
-fn read_entry(from: &mut Value) -> ExiftoolEntry {
+/// Type name and usual extension of binary data, guessed by its magic bytes
+pub fn detect_binary_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    const MAGIC: [(&[u8], &str, &str); 10] = [
+        (b"\xFF\xD8\xFF", "JPEG", "jpeg"),
+        (b"\x89PNG\r\n\x1A\n", "PNG", "png"),
+        (b"II*\0", "TIFF", "tif"),
+        (b"MM\0*", "TIFF", "tif"),
+        (b"GIF8", "GIF", "gif"),
+        (b"%PDF", "PDF", "pdf"),
+        (b"8BPS", "Photoshop", "psd"),
+        (b"<?xpacket", "XMP", "xmp"),
+        (b"<x:xmpmeta", "XMP", "xmp"),
+        (b"\xEF\xBB\xBF<?xpacket", "XMP", "xmp"),
+    ];
+    if let Some((_, name, ext)) = MAGIC.iter().find(|(magic, _, _)| bytes.starts_with(magic)) {
+        return Some((name, ext));
+    }
+    if bytes.get(36..40) == Some(b"acsp") {
+        return Some(("ICC profile", "icc"));
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return Some(("WebP", "webp"));
+    }
+    if bytes.get(4..8) == Some(b"ftyp") {
+        return match bytes.get(8..12) {
+            Some(b"heic" | b"heix" | b"mif1") => Some(("HEIF", "heic")),
+            _ => Some(("ISO media", "mp4")),
+        };
+    }
+    None
+}
+
+/// Executable looked up in PATH, unless another one is given with `--exiftool-path`
+pub const DEFAULT_EXIFTOOL: &str = "exiftool";
+
+/// Exiftool's placeholder for binary values, e.g. `(Binary data 1234 bytes, use -b option to extract)`
+static BINARY_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\(Binary data (\d+) bytes(, use -b option to extract)?\)$").unwrap()
+});
+
+/// Filter term comparing numerical value of a tag, e.g. `ISO>1600` or `FocalLength<=35`
+static NUMERIC_TERM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\w+)(>=|<=|>|<)(.*)$").unwrap());
+
+/// Reads plain numbers, rationals like `1/200` and numbers followed by a unit like `35.0 mm`
+pub fn parse_number(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let first = s.split_whitespace().next()?;
+    [s, first].iter().find_map(|s| match s.split_once('/') {
+        Some((num, den)) => {
+            let den: f64 = den.parse().ok()?;
+            (den != 0.0).then_some(num.parse::<f64>().ok()? / den)
+        }
+        None => s.parse().ok(),
+    })
+}
+
+/// Text typed in a filter as `check_filter` matches it against names and values, without kind tokens
+/// and numeric terms. `None` if there is no such text or it is a family filter like `<<xmp>>`
+pub fn filter_text(filter: &str) -> Option<String> {
+    let text = filter
+        .to_lowercase()
+        .split(' ')
+        .filter(|term| {
+            !matches!(*term, ":array" | ":binary" | ":empty") && numeric_term(term).is_none()
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let is_family = text.starts_with("<<") && text.ends_with(">>");
+    (!text.is_empty() && !is_family).then_some(text)
+}
+
+/// Tag, operator and threshold of a term like `iso>1600`. Terms like `a<b` or `iso>`
+/// have no number to compare with, they are plain text
+fn numeric_term(term: &str) -> Option<(&str, &str, f64)> {
+    let caps = NUMERIC_TERM.captures(term)?;
+    let threshold = parse_number(caps.get(3)?.as_str())?;
+    Some((caps.get(1)?.as_str(), caps.get(2)?.as_str(), threshold))
+}
+
+/// Term which was likely meant as a numeric comparison but is searched as text, e.g. `iso>` or `iso>16o0`
+pub fn filter_note(filter: &str) -> Option<String> {
+    filter.split(' ').find_map(|term| {
+        let caps = NUMERIC_TERM.captures(term)?;
+        if numeric_term(term).is_some() {
+            None
+        } else if caps[3].is_empty() {
+            Some(format!(
+                "'{term}' is searched as text until a number follows"
+            ))
+        } else if caps[3].starts_with(|c: char| c.is_ascii_digit()) {
+            Some(format!(
+                "'{term}' is searched as text, '{}' is not a number",
+                &caps[3]
+            ))
+        } else {
+            None
+        }
+    })
+}
+
+/// Size of binary data if the value is exiftool's binary placeholder
+fn binary_size_kb(val: &str) -> Option<f32> {
+    let num_bytes: f32 = BINARY_PLACEHOLDER.captures(val)?[1].parse().ok()?;
+    Some(num_bytes / 1024f32)
+}
+
+/// Bytes as space separated hex pairs, e.g. `ff d8 ff`
+pub fn bytes_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn read_entry(from: &mut Value) -> std::io::Result<ExiftoolEntry> {
     let mut res = ExiftoolEntry {
         file_name: PathBuf::new(),
         tag_entries: vec![],
+        snapshot: false,
     };
-    for (k, v) in from.as_object_mut().unwrap() {
+    let Some(tags) = from.as_object_mut() else {
+        return Err(std::io::Error::other(format!(
+            "expected an object of tags, got {}",
+            output_excerpt(from.to_string().as_bytes())
+        )));
+    };
+    for (k, v) in tags {
         if let Value::String(s) = v {
             if k.contains("SourceFile") {
                 res.file_name = PathBuf::from(&s);
@@ -280,6 +662,7 @@ fn read_entry(from: &mut Value) -> ExiftoolEntry {
         if !matches!(&v, Value::Object(_)) {
             continue;
         }
+        // serde_json's `arbitrary_precision` keeps numbers as exiftool printed them, e.g. `2.80`
         if let Value::Number(num) = &v["num"] {
             v["num"] = Value::String(num.to_string());
         }
@@ -292,7 +675,8 @@ fn read_entry(from: &mut Value) -> ExiftoolEntry {
         if let Value::Bool(num) = &v["val"] {
             v["val"] = Value::String(num.to_string());
         }
-        let mut entry: TagEntry = serde_json::from_value(v.clone()).unwrap();
+        let mut entry: TagEntry = serde_json::from_value(v.clone())
+            .map_err(|e| std::io::Error::other(format!("bad value of tag {k}: {e}")))?;
         if let Some(sep_pos) = k.find(":") {
             entry.instance = k[..sep_pos].to_owned();
             entry.short_name = k[sep_pos + 1..].to_owned();
@@ -300,42 +684,290 @@ fn read_entry(from: &mut Value) -> ExiftoolEntry {
             entry.short_name = k.clone();
         }
         if let EtVal::String(s) = &entry.val {
-            if s.contains("bytes") {
-                let num_bytes: f32 = s
-                    .chars()
-                    .filter(|ch| ch.is_ascii_digit())
-                    .collect::<String>()
-                    .parse()
-                    .unwrap();
-                entry.binary_size_kb = Some(num_bytes / 1024f32);
-            }
+            entry.binary_size_kb = binary_size_kb(s);
         }
         res.tag_entries.push(entry);
     }
-    res
+    Ok(res)
 }
 
-pub fn run(input: Vec<PathBuf>, recursive: bool) -> std::io::Result<Vec<ExiftoolEntry>> {
-    let mut et_cmd = Command::new("exiftool");
-    et_cmd
-        .args(input)
-        .arg("-j")
-        .arg("-G4")
-        .arg("-l")
-        .arg("-D")
-        .arg("-t");
-    if recursive {
-        et_cmd.arg("-r");
+/// Start of raw exiftool output for error messages, long output is cut
+fn output_excerpt(raw: &[u8]) -> String {
+    const MAX_LEN: usize = 200;
+    let excerpt = String::from_utf8_lossy(&raw[..raw.len().min(MAX_LEN)]);
+    if raw.len() > MAX_LEN {
+        format!("{}...", excerpt.trim())
+    } else {
+        excerpt.trim().to_owned()
     }
-    let et_out = et_cmd.output().unwrap();
+}
 
-    let mut res = Vec::new();
-    let mut sval: Value = serde_json::from_slice(&et_out.stdout).unwrap();
-    for file_out in sval.as_array_mut().unwrap() {
-        res.push(read_entry(file_out));
+/// Reads JSON printed by `exiftool -j`, which is an array of files; a single file object is accepted as well
+pub fn parse_output(raw: &[u8]) -> std::io::Result<Vec<ExiftoolEntry>> {
+    let bad_output = |reason: String| {
+        std::io::Error::other(format!(
+            "Unexpected exiftool output ({reason}): {}",
+            output_excerpt(raw)
+        ))
+    };
+    let mut sval: Value = serde_json::from_slice(raw).map_err(|e| bad_output(e.to_string()))?;
+    let files = match &mut sval {
+        Value::Array(files) => files.iter_mut().map(read_entry).collect(),
+        file @ Value::Object(_) => read_entry(file).map(|file| vec![file]),
+        _ => return Err(bad_output(String::from("not a list of files"))),
+    };
+    files.map_err(|e| bad_output(e.to_string()))
+}
+
+/// Runs exiftool once with `args` to modify `files` in place. Result of each file, in order of `files`,
+/// reads like exiftool's summary of a run on that file alone, e.g. `1 image files updated`
+pub fn write(
+    exiftool: &Path,
+    args: &[String],
+    files: &[PathBuf],
+) -> Result<Vec<Result<String, String>>, String> {
+    let lists = tempfile::tempdir().map_err(|e| format!("Failed to create temp folder: {e}"))?;
+    let updated = lists.path().join("updated");
+    let unchanged = lists.path().join("unchanged");
+    let out = Command::new(exiftool)
+        .args(args)
+        // Names of updated and unchanged files are saved to these lists
+        .arg("-efile8")
+        .arg(&updated)
+        .arg("-efile2")
+        .arg(&unchanged)
+        .arg("--")
+        .args(files)
+        .output()
+        .map_err(|e| format!("Failed to run exiftool: {e}"))?;
+    let read_list = |path: &Path| std::fs::read_to_string(path).unwrap_or_default();
+    Ok(write_results(
+        files,
+        &read_list(&updated),
+        &read_list(&unchanged),
+        &String::from_utf8_lossy(&out.stderr),
+    ))
+}
+
+/// Files missing from both lists failed, with the reason taken from exiftool's
+/// messages ending with their name, e.g. `Error: Not a valid JPG - a.jpg`,
+/// or from messages not naming any file
+fn write_results(
+    files: &[PathBuf],
+    updated: &str,
+    unchanged: &str,
+    stderr: &str,
+) -> Vec<Result<String, String>> {
+    let names = files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>();
+    let suffixes = names
+        .iter()
+        .map(|name| format!(" - {name}"))
+        .collect::<Vec<_>>();
+    let lines = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let general = lines
+        .iter()
+        .filter(|line| {
+            !suffixes
+                .iter()
+                .any(|suffix| line.ends_with(suffix.as_str()))
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    let listed = |list: &str, name: &str| list.lines().any(|line| line == name);
+    names
+        .iter()
+        .zip(&suffixes)
+        .map(|(name, suffix)| {
+            if listed(updated, name) {
+                return Ok(String::from("1 image files updated"));
+            }
+            if listed(unchanged, name) {
+                return Ok(String::from(
+                    "0 image files updated, 1 image files unchanged",
+                ));
+            }
+            let messages = lines
+                .iter()
+                .filter_map(|line| line.strip_suffix(suffix.as_str()))
+                .collect::<Vec<_>>();
+            Err(match (messages.as_slice(), general.as_slice()) {
+                ([], []) => String::from("File was not updated"),
+                ([], general) => general.join(", "),
+                (messages, _) => messages.join(", "),
+            })
+        })
+        .collect()
+}
+
+/// Extensions of metadata-only files, exiftool reads them like any other file
+const SIDECAR_EXTENSIONS: [&str; 4] = ["xmp", "mie", "exv", "exif"];
+
+/// Whether file holds metadata only, with no image to extract binary data from
+pub fn is_sidecar(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SIDECAR_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Shell-like form of a `write` call, shown to user before running it
+pub fn write_command_line(args: &[String], files: &[PathBuf]) -> String {
+    let quote = |arg: String| {
+        if arg.is_empty() || arg.contains([' ', '"', '\'', '\\']) {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        } else {
+            arg
+        }
+    };
+    std::iter::once(String::from("exiftool"))
+        .chain(args.iter().cloned().map(quote))
+        .chain(std::iter::once(String::from("--")))
+        .chain(files.iter().map(|f| quote(f.display().to_string())))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Turns user input like `+1:00:00` or `-0:0:1 12:00:00` into exiftool's `-AllDates` shift arg.
+/// Accepts `H:M[:S]` or `Y:M:D H:M:S`, an optional sign picks the direction (forward by default)
+pub fn time_shift_arg(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    let (op, offset) = match input.strip_prefix('-') {
+        Some(rest) => ("-=", rest),
+        None => ("+=", input.strip_prefix('+').unwrap_or(input)),
+    };
+    let is_numbers = |part: &str, min: usize, max: usize| {
+        let nums = part.split(':').collect::<Vec<_>>();
+        (min..=max).contains(&nums.len())
+            && nums
+                .iter()
+                .all(|n| !n.is_empty() && n.chars().all(|ch| ch.is_ascii_digit()))
+    };
+    let valid = match offset.split_once(' ') {
+        Some((date, time)) => is_numbers(date, 3, 3) && is_numbers(time, 3, 3),
+        None => is_numbers(offset, 2, 3),
+    };
+    if valid {
+        Ok(format!("-AllDates{op}{offset}"))
+    } else {
+        Err(format!(
+            "'{input}' is not a valid time shift, expected e.g. +1:00:00 or -0:0:1 0:00:00"
+        ))
     }
+}
 
-    Ok(res)
+/// Tags of the place nearest to GPS position of `file`, from exiftool's bundled geolocation
+/// database. Exiftool before 12.78 doesn't have it and gives no tags
+pub fn geolocation_tags(exiftool: &Path, file: &Path) -> std::io::Result<Vec<TagEntry>> {
+    let out = Command::new(exiftool)
+        .args([
+            "-j",
+            "-l",
+            "-D",
+            "-t",
+            "-api",
+            "geolocation",
+            "-Geolocation*",
+            "--",
+        ])
+        .arg(file)
+        .stdin(Stdio::null())
+        .output()?;
+    let mut sval: Value = serde_json::from_slice(&out.stdout).map_err(std::io::Error::other)?;
+    Ok(sval
+        .as_array_mut()
+        .and_then(|files| files.first_mut())
+        .filter(|file| file.is_object())
+        .and_then(|file| read_entry(file).ok())
+        .map(|file| file.tag_entries)
+        .unwrap_or_default())
+}
+
+/// Exiftool running in background, its output is collected by a separate thread
+pub struct EtJob {
+    child: Child,
+    output: JoinHandle<Vec<u8>>,
+    errors: JoinHandle<Vec<u8>>,
+}
+
+impl EtJob {
+    /// With `structured` XMP structures come as nested values instead of flattened tags
+    pub fn spawn(
+        exiftool: &Path,
+        input: Vec<PathBuf>,
+        recursive: bool,
+        structured: bool,
+    ) -> std::io::Result<Self> {
+        let mut et_cmd = Command::new(exiftool);
+        et_cmd.arg("-j").arg("-G4").arg("-l").arg("-D").arg("-t");
+        if recursive {
+            et_cmd.arg("-r");
+        }
+        if structured {
+            et_cmd.arg("-struct");
+        }
+        // Everything after `--` is a file name, even if it starts with a dash
+        et_cmd.arg("--").args(input);
+        let mut child = et_cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let output = drain(child.stdout.take().expect("stdout is piped"));
+        let errors = drain(child.stderr.take().expect("stderr is piped"));
+        Ok(Self {
+            child,
+            output,
+            errors,
+        })
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    pub fn kill(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    /// Waits for exiftool to exit and reads its output
+    pub fn finish(mut self) -> std::io::Result<Vec<ExiftoolEntry>> {
+        self.child.wait()?;
+        let stdout = self.output.join().unwrap_or_default();
+        let stderr = self.errors.join().unwrap_or_default();
+        parse_output(&stdout).map_err(|e| {
+            // Exiftool explains failures like missing files only on stderr
+            let reason = String::from_utf8_lossy(&stderr);
+            match reason.trim() {
+                "" => e,
+                reason => std::io::Error::new(e.kind(), format!("{reason} ({e})")),
+            }
+        })
+    }
+}
+
+/// Pipe has to be drained while exiftool runs, or it blocks on a full buffer
+fn drain(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+pub fn run(
+    exiftool: &Path,
+    input: Vec<PathBuf>,
+    recursive: bool,
+    structured: bool,
+) -> std::io::Result<Vec<ExiftoolEntry>> {
+    EtJob::spawn(exiftool, input, recursive, structured)?.finish()
 }
 
 // #[test]
@@ -370,6 +1002,288 @@ pub fn run(input: Vec<PathBuf>, recursive: bool) -> std::io::Result<Vec<Exiftool
 //     }
 // }
 
+#[test]
+fn detect_binary_type_test() {
+    assert_eq!(
+        detect_binary_type(b"\xFF\xD8\xFF\xE1\x00\x10Exif"),
+        Some(("JPEG", "jpeg"))
+    );
+    assert_eq!(
+        detect_binary_type(b"II*\0\x08\0\0\0"),
+        Some(("TIFF", "tif"))
+    );
+    let mut icc = vec![0u8; 36];
+    icc.extend_from_slice(b"acsp");
+    assert_eq!(detect_binary_type(&icc), Some(("ICC profile", "icc")));
+    assert_eq!(detect_binary_type(b"not a known format"), None);
+    assert_eq!(detect_binary_type(b""), None);
+}
+
+#[test]
+fn read_entry_precision_test() {
+    // As printed by `exiftool -j -G4 -l -D -t`, numbers should be shown exactly like this
+    let mut out: Value = serde_json::from_str(
+        r#"{
+            "SourceFile": "a.jpg",
+            "ExposureTime": {"id": 33434, "table": "Exif::Main", "desc": "Exposure Time", "val": "1/8000", "num": 0.000125},
+            "FNumber": {"id": 33437, "table": "Exif::Main", "desc": "F Number", "val": 2.80, "num": 2.80},
+            "GPSLatitude": {"id": 2, "table": "Exif::GPS", "desc": "GPS Latitude", "val": "55 deg 45' 7.20\" N", "num": 55.7520000000000003},
+            "ImageUniqueID": {"id": 42016, "table": "Exif::Main", "desc": "Image Unique ID", "val": 123456789012345678901234},
+            "Weights": {"id": "x", "table": "XMP::x", "desc": "Weights", "val": [1.10, 2, 1e-7]}
+        }"#,
+    )
+    .unwrap();
+    let entry = read_entry(&mut out).unwrap();
+    let shown = |short_name: &str| {
+        let entry = entry
+            .tag_entries
+            .iter()
+            .find(|e| e.short_name == short_name)
+            .unwrap();
+        (entry.val.to_string(), entry.display_value(true))
+    };
+    assert_eq!(shown("ExposureTime").1, "0.000125");
+    assert_eq!(
+        shown("FNumber"),
+        (String::from("2.80"), String::from("2.80"))
+    );
+    assert_eq!(shown("GPSLatitude").1, "55.7520000000000003");
+    assert_eq!(shown("ImageUniqueID").0, "123456789012345678901234");
+    assert_eq!(shown("Weights").0, "1.10 2 1e-7");
+    assert_eq!(entry.tag_entries[0].id, Some(33434));
+}
+
+#[test]
+fn to_json_test() {
+    let mut out: Value = serde_json::from_str(
+        r#"{
+            "SourceFile": "a.jpg",
+            "Make": {"id": 271, "table": "Exif::Main", "desc": "Make", "val": "Canon"},
+            "Copy1:Make": {"id": 271, "table": "Exif::IFD1", "desc": "Make", "val": "Nikon"},
+            "Orientation": {"id": 274, "table": "Exif::Main", "desc": "Orientation", "val": "Rotate 90 CW", "num": 6},
+            "Subject": {"id": "subject", "table": "XMP::dc", "desc": "Subject", "val": ["cat", "dog"]},
+            "ThumbnailImage": {"id": 513, "table": "Exif::IFD1", "desc": "Thumbnail Image", "val": "(Binary data 2048 bytes, use -b option to extract)", "index": 1}
+        }"#,
+    )
+    .unwrap();
+    let entry = read_entry(&mut out).unwrap();
+    let restored = read_entry(&mut entry.to_json()).unwrap();
+    assert_eq!(restored.file_name, entry.file_name);
+    assert_eq!(restored.tag_entries, entry.tag_entries);
+    let copy = restored
+        .tag_entries
+        .iter()
+        .find(|e| e.instance == "Copy1")
+        .unwrap();
+    assert_eq!(
+        (copy.short_name.as_str(), copy.table_to_string()),
+        ("Make", String::from("Exif::IFD1"))
+    );
+    assert!(restored
+        .tag_entries
+        .iter()
+        .any(|e| e.binary_size_kb == Some(2.0)));
+}
+
+#[test]
+fn array_elements_test() {
+    let val: EtVal =
+        serde_json::from_str(r#"["red", 2, 1.50, true, null, {"a": 1}, "two words"]"#).unwrap();
+    assert_eq!(
+        val.elements(),
+        vec!["red", "2", "1.50", "true", "null", "{a=1}", "two words"]
+    );
+    assert_eq!(val.to_string(), "red 2 1.50 true null {a=1} two words");
+    assert!(val.check_filter("red"));
+    assert!(val.check_filter("two words"));
+    assert!(!val.check_filter("\"red\""));
+    let val = EtVal::String(String::from("Red"));
+    assert_eq!(val.elements(), vec!["Red"]);
+    assert!(val.check_filter("red"));
+}
+
+#[test]
+fn structured_value_test() {
+    let val: EtVal = serde_json::from_str(
+        r#"[{"Name": "Alice", "Area": {"X": 0.5, "Y": 0.25}}, {"Name": "Bob", "Roles": ["a", "b"]}]"#,
+    )
+    .unwrap();
+    assert!(val.is_structured());
+    assert_eq!(
+        val.to_string(),
+        "{Area={X=0.5,Y=0.25},Name=Alice} {Name=Bob,Roles=[a,b]}"
+    );
+    assert_eq!(
+        val.tree_lines(),
+        vec![
+            "[0]:",
+            "  Area:",
+            "    X: 0.5",
+            "    Y: 0.25",
+            "  Name: Alice",
+            "[1]:",
+            "  Name: Bob",
+            "  Roles:",
+            "    [0]: a",
+            "    [1]: b",
+        ]
+    );
+    assert!(val.check_filter("alice"));
+
+    let val: EtVal = serde_json::from_str(r#"{"City": "Paris"}"#).unwrap();
+    assert_eq!(
+        val,
+        EtVal::Object(serde_json::from_str(r#"{"City": "Paris"}"#).unwrap())
+    );
+    assert_eq!(val.tree_lines(), vec!["City: Paris"]);
+    assert!(!EtVal::String(String::from("a")).is_structured());
+}
+
+#[test]
+fn binary_size_kb_test() {
+    assert_eq!(
+        binary_size_kb("(Binary data 2048 bytes, use -b option to extract)"),
+        Some(2.0)
+    );
+    assert_eq!(binary_size_kb("(Binary data 512 bytes)"), Some(0.5));
+    assert_eq!(binary_size_kb("Uses 2 of 8 bytes"), None);
+    assert_eq!(binary_size_kb("comment about bytes"), None);
+    assert_eq!(
+        binary_size_kb("see (Binary data 10 bytes, use -b option to extract)"),
+        None
+    );
+    assert_eq!(binary_size_kb("(Binary data many bytes)"), None);
+}
+
+#[test]
+fn time_shift_arg_test() {
+    assert_eq!(time_shift_arg("+1:00:00").unwrap(), "-AllDates+=1:00:00");
+    assert_eq!(time_shift_arg("1:30").unwrap(), "-AllDates+=1:30");
+    assert_eq!(
+        time_shift_arg(" -0:0:1 12:00:00 ").unwrap(),
+        "-AllDates-=0:0:1 12:00:00"
+    );
+    assert!(time_shift_arg("").is_err());
+    assert!(time_shift_arg("+1h").is_err());
+    assert!(time_shift_arg("1:2:3:4").is_err());
+    assert!(time_shift_arg("-1:: 1:00:00").is_err());
+}
+
+#[test]
+fn write_command_line_test() {
+    let line = write_command_line(
+        &[String::from("-AllDates+=0:0:1 12:00:00")],
+        &[PathBuf::from("my photo.jpg"), PathBuf::from("-a.jpg")],
+    );
+    assert_eq!(
+        line,
+        "exiftool '-AllDates+=0:0:1 12:00:00' -- 'my photo.jpg' -a.jpg"
+    );
+}
+
+#[test]
+fn is_sidecar_test() {
+    assert!(is_sidecar(Path::new("IMG_001.xmp")));
+    assert!(is_sidecar(Path::new("dir/IMG_001.CR2.XMP")));
+    assert!(is_sidecar(Path::new("meta.mie")));
+    assert!(!is_sidecar(Path::new("IMG_001.jpg")));
+    assert!(!is_sidecar(Path::new("xmp")));
+}
+
+#[test]
+fn command_template_test() {
+    let entry = test_entry("Creator", "XMP::dc", "John Doe", None);
+    let file = Path::new("my photo.jpg");
+
+    assert_eq!(
+        entry.command_template('d', file).as_deref(),
+        Some("exiftool -XMP:Creator= -- 'my photo.jpg'")
+    );
+    assert_eq!(
+        entry.command_template('w', file).as_deref(),
+        Some("exiftool '-XMP:Creator=John Doe' -- 'my photo.jpg'")
+    );
+    assert_eq!(
+        entry.command_template('r', file).as_deref(),
+        Some("exiftool -a -G1 -s -XMP:Creator -- 'my photo.jpg'")
+    );
+    assert_eq!(entry.command_template('x', file), None);
+}
+
+#[test]
+fn check_filter_kind_tokens_test() {
+    let entry = |val: Value| test_entry("Keywords", "XMP::dc", val, None);
+    let array = entry(serde_json::json!(["cat", "dog"]));
+    let empty = entry(serde_json::json!(" "));
+    let mut binary = entry(serde_json::json!("(Binary data 10 bytes)"));
+    binary.binary_size_kb = Some(0.01);
+
+    assert!(array.check_filter(":array", false));
+    assert!(array.check_filter(":array dog", false));
+    assert!(!array.check_filter(":array bird", false));
+    assert!(!array.check_filter(":empty", false));
+    assert!(empty.check_filter(":EMPTY", false));
+    assert!(!empty.check_filter(":array", false));
+    assert!(binary.check_filter(":binary keywords", false));
+    assert!(!array.check_filter(":binary", false));
+    assert!(!array.check_filter(":unknown", false));
+}
+
+#[test]
+fn numeric_filter_test() {
+    let entry = |short_name: &str, val: &str, num: &str| {
+        test_entry(short_name, "Exif::Main", val, Some(num))
+    };
+    let iso = entry("ISO", "3200", "3200");
+    let focal = entry("FocalLength", "24.0 mm", "24");
+    let exposure = entry("ExposureTime", "1/200", "0.005");
+
+    assert!(iso.check_filter("ISO>1600", false));
+    assert!(!iso.check_filter("iso<1600", false));
+    assert!(iso.check_filter("iso>=3200 iso", false));
+    assert!(!iso.check_filter("iso>=3200 model", false));
+    assert!(!focal.check_filter("iso>1600", false));
+    assert!(focal.check_filter("FocalLength<35", false));
+    assert!(exposure.check_filter("exposuretime<=1/100", false));
+    assert!(!exposure.check_filter("exposuretime>1/100", false));
+    assert!(!iso.check_filter("iso>many", false));
+    // Without a number to compare with, it's text
+    let formula = entry("Formula", "A<B", "A<B");
+    assert!(formula.check_filter("a<b", false));
+    assert!(!formula.check_filter("formula<1", false));
+
+    assert_eq!(parse_number("24.0 mm"), Some(24.0));
+    assert_eq!(parse_number("1/4"), Some(0.25));
+    assert_eq!(parse_number("1/0"), None);
+    assert_eq!(filter_note("iso>1600 <<exif>>"), None);
+    assert!(filter_note("iso>16o0").is_some());
+    assert_eq!(filter_note("a<b"), None);
+    assert!(filter_note("iso>").is_some());
+}
+
+#[test]
+fn hex_test() {
+    let entry = |val: Value, num: Option<&str>| test_entry("Tag", "Exif::Main", val, num);
+    assert_eq!(
+        entry("Horizontal (normal)".into(), Some("1")).integer_hex(),
+        Some(String::from("0x1"))
+    );
+    assert_eq!(
+        entry("255".into(), None).integer_hex(),
+        Some(String::from("0xFF"))
+    );
+    assert_eq!(
+        entry(serde_json::json!([0, 2, 3, 1]), None).integer_hex(),
+        Some(String::from("0x0 0x2 0x3 0x1"))
+    );
+    assert_eq!(
+        entry("-16".into(), None).integer_hex(),
+        Some(String::from("-0x10"))
+    );
+    assert_eq!(entry("1/200".into(), Some("0.005")).integer_hex(), None);
+    assert_eq!(bytes_hex(b"\xFF\xD8\x00"), "ff d8 00");
+}
+
 #[test]
 fn t_serde() {
     let image_path = "/Users/mikhailmatsykh/Downloads/2024-09-06 175947.dng";
@@ -423,3 +1337,76 @@ fn t_serde() {
         println!("\n{:?}", entry);
     }
 }
+
+#[test]
+fn parse_output_test() {
+    let tag = r#"{"id": 271, "table": "Exif::Main", "desc": "Make", "val": "Canon"}"#;
+    let files =
+        parse_output(format!(r#"[{{"SourceFile": "a.jpg", "Make": {tag}}}]"#).as_bytes()).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].file_name, PathBuf::from("a.jpg"));
+    // Single file object instead of an array
+    let files =
+        parse_output(format!(r#"{{"SourceFile": "b.jpg", "Make": {tag}}}"#).as_bytes()).unwrap();
+    assert_eq!(files[0].file_name, PathBuf::from("b.jpg"));
+    assert!(parse_output(b"[]").unwrap().is_empty());
+
+    // Warning printed before JSON
+    let err = parse_output(b"Warning: File is empty - empty.jpg\n[{}]").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Warning: File is empty - empty.jpg"));
+    let err = parse_output(br#"[{"SourceFile": "a.jpg", "Make": {"val": 1}}]"#).unwrap_err();
+    assert!(err.to_string().contains("bad value of tag Make"));
+    assert!(parse_output(b"[42]").is_err());
+    assert!(parse_output(b"").is_err());
+    // Long output is cut
+    let err = parse_output("x".repeat(1000).as_bytes()).unwrap_err();
+    assert!(err.to_string().len() < 300);
+    assert!(read_entry(&mut Value::Null).is_err());
+}
+
+#[test]
+fn filter_text_test() {
+    assert_eq!(filter_text("Canon EOS"), Some(String::from("canon eos")));
+    assert_eq!(
+        filter_text(":array Lens iso>100"),
+        Some(String::from("lens"))
+    );
+    assert_eq!(filter_text(":binary"), None);
+    assert_eq!(filter_text("a<b"), Some(String::from("a<b")));
+    assert_eq!(filter_text("<<XMP>>"), None);
+    assert_eq!(filter_text(""), None);
+}
+
+#[test]
+fn write_results_test() {
+    let files = ["a.jpg", "b.jpg", "c.png", "d.jpg"].map(PathBuf::from);
+    let results = write_results(
+        &files,
+        "a.jpg\n",
+        "b.jpg\n",
+        "Error: Not a valid PNG (looks more like a JPEG) - c.png\n\
+         Warning: [minor] Bad MakerNotes - c.png\n",
+    );
+    assert_eq!(results[0], Ok(String::from("1 image files updated")));
+    assert_eq!(
+        results[1],
+        Ok(String::from(
+            "0 image files updated, 1 image files unchanged"
+        ))
+    );
+    assert_eq!(
+        results[2],
+        Err(String::from(
+            "Error: Not a valid PNG (looks more like a JPEG), Warning: [minor] Bad MakerNotes"
+        ))
+    );
+    assert_eq!(results[3], Err(String::from("File was not updated")));
+    // Messages not naming a file concern all of them
+    let results = write_results(&files[..1], "", "", "Invalid TAG name: \"Bad Tag\"\n");
+    assert_eq!(
+        results,
+        vec![Err(String::from("Invalid TAG name: \"Bad Tag\""))]
+    );
+}
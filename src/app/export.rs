@@ -0,0 +1,210 @@
+use std::{collections::BTreeMap, path::Path};
+
+use rust_xlsxwriter::{Color, Format, Workbook, XlsxError};
+use serde_json::Value;
+
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::{analysis::values_differ, et_wrapper::TagEntry};
+
+/// Characters excel doesn't allow in sheet names
+const SHEET_NAME_FORBIDDEN: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+const SHEET_NAME_MAX_LEN: usize = 31;
+
+type CompareRow = (TagEntry, Vec<Option<TagEntry>>);
+
+/// Joins rows into CSV text, quoting fields that need it
+pub fn to_csv(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| {
+                    if field.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Tags as a json array, one object with names, ID, family and both values per tag
+pub fn tags_to_json(entries: &[&TagEntry]) -> Value {
+    entries
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "name": entry.name,
+                "short_name": entry.short_name,
+                "id": entry.id,
+                "table": entry.table_to_string(),
+                "val": entry.val,
+                "num": entry.num,
+            })
+        })
+        .collect()
+}
+
+/// Compare grid as CSV, tag names in the first column followed by one column per file.
+/// Tags missing in a file are left empty
+pub fn compare_to_csv(
+    files: &[&Path],
+    rows: &[&CompareRow],
+    short_names: bool,
+    numerical: bool,
+) -> String {
+    let header = std::iter::once(String::from("Tag"))
+        .chain(files.iter().map(|file| file.display().to_string()))
+        .collect();
+    let lines = rows.iter().map(|(key_entry, values)| {
+        std::iter::once(if short_names {
+            key_entry.short_name.clone()
+        } else {
+            key_entry.name.clone()
+        })
+        .chain(values.iter().map(|value| {
+            value
+                .as_ref()
+                .map(|entry| entry.display_value(numerical))
+                .unwrap_or_default()
+        }))
+        .collect()
+    });
+    to_csv(&std::iter::once(header).chain(lines).collect::<Vec<_>>())
+}
+
+/// Writes compare grid with one sheet per tag family, rows that differ are highlighted
+pub fn compare_to_xlsx(
+    path: &Path,
+    files: &[&Path],
+    rows: &[&CompareRow],
+    short_names: bool,
+    numerical: bool,
+) -> Result<(), XlsxError> {
+    let mut families: BTreeMap<&str, Vec<&CompareRow>> = BTreeMap::new();
+    for row in rows {
+        families
+            .entry(row.0.table.0.as_str())
+            .or_default()
+            .push(row);
+    }
+
+    let header_format = Format::new().set_bold().set_background_color(Color::Silver);
+    let diff_format = Format::new().set_background_color(Color::RGB(0xFFF2CC));
+    let missing_format = Format::new().set_background_color(Color::RGB(0xD9D9D9));
+
+    let mut workbook = Workbook::new();
+    for (family, family_rows) in families {
+        let sheet = workbook.add_worksheet();
+        let sheet_name = family
+            .chars()
+            .filter(|ch| !SHEET_NAME_FORBIDDEN.contains(ch))
+            .take(SHEET_NAME_MAX_LEN)
+            .collect::<String>();
+        sheet.set_name(if sheet_name.is_empty() {
+            "Unknown"
+        } else {
+            &sheet_name
+        })?;
+
+        sheet.write_with_format(0, 0, "Tag", &header_format)?;
+        for (col, file) in files.iter().enumerate() {
+            sheet.write_with_format(
+                0,
+                col as u16 + 1,
+                file.to_string_lossy().as_ref(),
+                &header_format,
+            )?;
+        }
+
+        for (i, (key_entry, values)) in family_rows.into_iter().enumerate() {
+            let row = i as u32 + 1;
+            sheet.write(
+                row,
+                0,
+                if short_names {
+                    &key_entry.short_name
+                } else {
+                    &key_entry.name
+                },
+            )?;
+            let differ = values_differ(values);
+            for (col, value) in values.iter().enumerate() {
+                let col = col as u16 + 1;
+                match value {
+                    Some(entry) if differ => {
+                        sheet.write_with_format(
+                            row,
+                            col,
+                            entry.display_value(numerical),
+                            &diff_format,
+                        )?;
+                    }
+                    Some(entry) => {
+                        sheet.write(row, col, entry.display_value(numerical))?;
+                    }
+                    None => {
+                        sheet.write_blank(row, col, &missing_format)?;
+                    }
+                }
+            }
+        }
+
+        sheet.set_freeze_panes(1, 1)?;
+        sheet.set_column_width(0, 32)?;
+        for col in 1..=files.len() as u16 {
+            sheet.set_column_width(col, 24)?;
+        }
+    }
+
+    workbook.save(path)
+}
+
+#[test]
+fn to_csv_test() {
+    let rows = vec![
+        vec![String::from("File"), String::from("Note")],
+        vec![String::from("a.jpg"), String::from("say \"hi\", twice")],
+    ];
+    assert_eq!(
+        to_csv(&rows),
+        "File,Note\na.jpg,\"say \"\"hi\"\", twice\"\n"
+    );
+}
+
+#[test]
+fn tags_to_json_test() {
+    let mut entry = test_entry("Orientation", "Exif::IFD0", "Rotate 90 CW", Some("6"));
+    entry.id = Some(274);
+    assert_eq!(
+        tags_to_json(&[&entry]),
+        serde_json::json!([{
+            "name": "Orientation",
+            "short_name": "Orientation",
+            "id": 274,
+            "table": "Exif::IFD0",
+            "val": "Rotate 90 CW",
+            "num": "6",
+        }])
+    );
+}
+
+#[test]
+fn compare_to_csv_test() {
+    let entry = |val: &str| {
+        let mut entry = test_entry("Model", "Exif::IFD0", val, None);
+        entry.name = String::from("Camera Model Name");
+        entry
+    };
+    let row = (entry("R5"), vec![Some(entry("R5")), None]);
+    let files = [Path::new("a.jpg"), Path::new("b, 2.jpg")];
+    assert_eq!(
+        compare_to_csv(&files, &[&row], true, false),
+        "Tag,a.jpg,\"b, 2.jpg\"\nModel,R5,\n"
+    );
+    assert!(compare_to_csv(&files, &[&row], false, false).contains("\nCamera Model Name,"));
+}
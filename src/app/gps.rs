@@ -0,0 +1,191 @@
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::et_wrapper::{parse_number, TagEntry};
+
+/// One line of GPS panel, readable form (DMS for coordinates) next to a plain decimal one
+pub struct GpsRow {
+    pub label: &'static str,
+    pub readable: String,
+    pub decimal: String,
+}
+
+/// GPS data of a file resolved from sibling tags, coordinates are signed (south and west negative)
+#[derive(Default)]
+pub struct GpsSummary {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub rows: Vec<GpsRow>,
+}
+
+impl GpsSummary {
+    /// Position as `lat,long`, the way mapping tools take it
+    pub fn lat_long(&self) -> Option<String> {
+        Some(format!("{:.6},{:.6}", self.latitude?, self.longitude?))
+    }
+}
+
+/// Coordinate as degrees, minutes and seconds, e.g. `51° 30' 26.00" N`
+pub fn to_dms(decimal: f64, positive: char, negative: char) -> String {
+    let hemisphere = if decimal < 0.0 { negative } else { positive };
+    let total_seconds = (decimal.abs() * 3600.0 * 100.0).round() / 100.0;
+    let degrees = (total_seconds / 3600.0).floor();
+    let minutes = ((total_seconds - degrees * 3600.0) / 60.0).floor();
+    let seconds = total_seconds - degrees * 3600.0 - minutes * 60.0;
+    format!("{degrees}° {minutes}' {seconds:.2}\" {hemisphere}")
+}
+
+/// Nearest place as exiftool's geolocation feature puts it, e.g. `Paris, Île-de-France, France`.
+/// Copied form leaves out distance to the place
+pub fn place_row(entries: &[TagEntry]) -> Option<GpsRow> {
+    let value = |short_name: &str| {
+        entries
+            .iter()
+            .find(|e| e.short_name == short_name)
+            .map(|e| e.val.to_string().trim().to_owned())
+            .filter(|v| !v.is_empty())
+    };
+    let place = ["GeolocationCity", "GeolocationRegion", "GeolocationCountry"]
+        .iter()
+        .filter_map(|tag| value(tag))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if place.is_empty() {
+        return None;
+    }
+    let readable = match value("GeolocationDistance") {
+        Some(distance) => format!("{place} ({distance} away)"),
+        None => place.clone(),
+    };
+    Some(GpsRow {
+        label: "Place",
+        readable,
+        decimal: place,
+    })
+}
+
+pub fn gps_summary(entries: &[TagEntry]) -> GpsSummary {
+    let find = |short_name: &str| {
+        // Composite tags already have ref tags applied
+        entries
+            .iter()
+            .filter(|e| e.short_name == short_name)
+            .max_by_key(|e| e.table.0 == "Composite")
+    };
+    let number =
+        |short_name: &str| find(short_name).and_then(|e| parse_number(&e.display_value(true)));
+    let reference = |short_name: &str| {
+        find(short_name)
+            .map(|e| e.display_value(true).trim().to_uppercase())
+            .unwrap_or_default()
+    };
+    let signed = |value: f64, ref_tag: &str, negative: &str| {
+        if value > 0.0 && reference(ref_tag).starts_with(negative) {
+            -value
+        } else {
+            value
+        }
+    };
+
+    let latitude = number("GPSLatitude").map(|v| signed(v, "GPSLatitudeRef", "S"));
+    let longitude = number("GPSLongitude").map(|v| signed(v, "GPSLongitudeRef", "W"));
+    let mut rows = Vec::new();
+    if let Some(lat) = latitude {
+        rows.push(GpsRow {
+            label: "Latitude",
+            readable: to_dms(lat, 'N', 'S'),
+            decimal: format!("{lat:.6}"),
+        });
+    }
+    if let Some(long) = longitude {
+        rows.push(GpsRow {
+            label: "Longitude",
+            readable: to_dms(long, 'E', 'W'),
+            decimal: format!("{long:.6}"),
+        });
+    }
+    // Ref of altitude is `1` for below sea level
+    if let Some(alt) = number("GPSAltitude").map(|v| signed(v, "GPSAltitudeRef", "1")) {
+        rows.push(GpsRow {
+            label: "Altitude",
+            readable: format!("{alt:.1} m"),
+            decimal: format!("{alt:.1}"),
+        });
+    }
+    for (label, tag, ref_tag) in [
+        ("Direction", "GPSImgDirection", "GPSImgDirectionRef"),
+        ("Speed", "GPSSpeed", "GPSSpeedRef"),
+    ] {
+        if let (Some(entry), Some(value)) = (find(tag), number(tag)) {
+            let unit = match (label, reference(ref_tag).as_str()) {
+                ("Direction", "M") => "° magnetic",
+                ("Direction", _) => "° true",
+                (_, "M") => " mph",
+                (_, "N") => " knots",
+                _ => " km/h",
+            };
+            rows.push(GpsRow {
+                label,
+                readable: format!("{}{unit}", entry.val.to_string().trim()),
+                decimal: format!("{value}"),
+            });
+        }
+    }
+
+    rows.extend(place_row(entries));
+
+    GpsSummary {
+        latitude,
+        longitude,
+        rows,
+    }
+}
+
+#[test]
+fn gps_summary_test() {
+    let entry = |short_name: &str, table: &str, val: &str, num: &str| {
+        test_entry(short_name, table, val, Some(num))
+    };
+    let entries = vec![
+        entry("GPSLatitudeRef", "Exif::GPS", "South", "S"),
+        entry("GPSLatitude", "Exif::GPS", "33 deg 51' 54.00\"", "33.865"),
+        entry("GPSLongitudeRef", "Exif::GPS", "East", "E"),
+        entry("GPSLongitude", "Exif::GPS", "151 deg 12' 36.00\"", "151.21"),
+        entry("GPSAltitudeRef", "Exif::GPS", "Below Sea Level", "1"),
+        entry("GPSAltitude", "Exif::GPS", "12 m", "12"),
+        entry("GPSSpeedRef", "Exif::GPS", "km/h", "K"),
+        entry("GPSSpeed", "Exif::GPS", "3.5", "3.5"),
+        entry("GeolocationCity", "Composite", "Sydney", "Sydney"),
+        entry("GeolocationCountry", "Composite", "Australia", "Australia"),
+        entry("GeolocationDistance", "Composite", "1.20 km", "1.20"),
+    ];
+    let summary = gps_summary(&entries);
+    assert_eq!(summary.lat_long().as_deref(), Some("-33.865000,151.210000"));
+    let rows = summary
+        .rows
+        .iter()
+        .map(|r| (r.label, r.readable.as_str(), r.decimal.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            ("Latitude", "33° 51' 54.00\" S", "-33.865000"),
+            ("Longitude", "151° 12' 36.00\" E", "151.210000"),
+            ("Altitude", "-12.0 m", "-12.0"),
+            ("Speed", "3.5 km/h", "3.5"),
+            (
+                "Place",
+                "Sydney, Australia (1.20 km away)",
+                "Sydney, Australia"
+            ),
+        ]
+    );
+
+    // Composite position is signed already and wins over Exif one
+    let entries = vec![
+        entry("GPSLatitude", "Exif::GPS", "10 deg", "10"),
+        entry("GPSLatitude", "Composite", "10 deg S", "-10"),
+        entry("GPSLatitudeRef", "Exif::GPS", "South", "S"),
+    ];
+    assert_eq!(gps_summary(&entries).latitude, Some(-10.0));
+    assert!(gps_summary(&[]).rows.is_empty());
+}
@@ -0,0 +1,155 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::et_wrapper::TagEntry;
+
+pub const BYTES_PER_ROW: u64 = 16;
+
+/// Binary data of a tag extracted to a temporary file, only shown rows are read from it
+pub struct HexView {
+    pub title: String,
+    file: File,
+    pub len: u64,
+    /// First shown byte, always at the start of a row
+    pub offset: u64,
+    /// Rows fitting on screen, set while drawing
+    pub page_rows: u64,
+    /// Offset being typed for "go to offset"
+    pub goto_input: Option<String>,
+}
+
+impl HexView {
+    pub fn open(entry: &TagEntry, exiftool: &Path, image_path: &Path) -> Result<Self, String> {
+        let file = tempfile::tempfile().map_err(|e| format!("Failed to create temp file: {e}"))?;
+        let out = file
+            .try_clone()
+            .map_err(|e| format!("Failed to create temp file: {e}"))?;
+        entry
+            .write_binary_to(exiftool, image_path, out)
+            .map_err(|_| String::from("Failed to extract binary data!"))?;
+        let len = file
+            .metadata()
+            .map_err(|e| format!("Failed to read extracted data: {e}"))?
+            .len();
+        if len == 0 {
+            return Err(String::from("Failed to extract binary data!"));
+        }
+        Ok(Self {
+            title: format!(
+                "{} of {}",
+                entry.short_name,
+                image_path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            file,
+            len,
+            offset: 0,
+            page_rows: 1,
+            goto_input: None,
+        })
+    }
+
+    /// Rows starting at `offset`, at most `count` of them
+    pub fn rows(&self, count: u64) -> Vec<(u64, Vec<u8>)> {
+        let mut bytes = Vec::new();
+        let mut file = &self.file;
+        if file.seek(SeekFrom::Start(self.offset)).is_ok() {
+            let _ = file.take(count * BYTES_PER_ROW).read_to_end(&mut bytes);
+        }
+        bytes
+            .chunks(BYTES_PER_ROW as usize)
+            .enumerate()
+            .map(|(i, chunk)| (self.offset + i as u64 * BYTES_PER_ROW, chunk.to_vec()))
+            .collect()
+    }
+
+    /// Offset of the last row which still fills the screen
+    fn max_offset(&self) -> u64 {
+        let last_row = self.len.saturating_sub(1) / BYTES_PER_ROW;
+        last_row.saturating_sub(self.page_rows.saturating_sub(1)) * BYTES_PER_ROW
+    }
+
+    pub fn scroll_rows(&mut self, delta: i64) {
+        let offset = self.offset as i64 + delta * BYTES_PER_ROW as i64;
+        self.go_to(offset.max(0) as u64);
+    }
+
+    pub fn scroll_pages(&mut self, delta: i64) {
+        self.scroll_rows(delta * self.page_rows.max(1) as i64);
+    }
+
+    /// Scrolls to the row holding byte at `offset`
+    pub fn go_to(&mut self, offset: u64) {
+        self.offset = (offset - offset % BYTES_PER_ROW).min(self.max_offset());
+    }
+
+    pub fn go_to_end(&mut self) {
+        self.offset = self.max_offset();
+    }
+
+    /// Jumps to offset typed in `goto_input`
+    pub fn confirm_goto(&mut self) -> Result<(), String> {
+        let input = self.goto_input.take().unwrap_or_default();
+        let offset = parse_offset(&input).ok_or_else(|| format!("'{input}' is not an offset!"))?;
+        if offset >= self.len {
+            return Err(format!("Offset {offset:#x} is past the end of data!"));
+        }
+        self.go_to(offset);
+        Ok(())
+    }
+}
+
+/// Offset as decimal, or hex with `0x` prefix
+fn parse_offset(input: &str) -> Option<u64> {
+    let input = input.trim();
+    match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+/// Row of hex dump: offset, bytes as hex and as ASCII, e.g.
+/// `00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|`
+pub fn hex_row(offset: u64, bytes: &[u8]) -> String {
+    let mut hex = String::new();
+    for i in 0..BYTES_PER_ROW as usize {
+        if i == BYTES_PER_ROW as usize / 2 {
+            hex.push(' ');
+        }
+        match bytes.get(i) {
+            Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+            None => hex.push_str("   "),
+        }
+    }
+    let ascii = bytes
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect::<String>();
+    format!("{offset:08x}  {hex} |{ascii}|")
+}
+
+#[test]
+fn hex_row_test() {
+    assert_eq!(
+        hex_row(0x10, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+        "00000010  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|"
+    );
+    assert_eq!(
+        hex_row(0, b"ab"),
+        format!("00000000  61 62 {}  |ab|", " ".repeat(3 * 14))
+    );
+    assert_eq!(parse_offset("0x1F"), Some(31));
+    assert_eq!(parse_offset(" 42 "), Some(42));
+    assert_eq!(parse_offset("zz"), None);
+}
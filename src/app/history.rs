@@ -0,0 +1,125 @@
+use serde_json::Value;
+
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::et_wrapper::{EtVal, TagEntry};
+
+/// One step of XMP edit history (`xmpMM:History`), fields missing in file are empty
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct HistoryEvent {
+    pub when: String,
+    pub action: String,
+    pub software_agent: String,
+    pub changed: String,
+    pub parameters: String,
+}
+
+/// Whether tag is one of flattened XMP history fields, e.g. `HistoryAction`
+pub fn is_history_tag(entry: &TagEntry) -> bool {
+    entry.table.0 == "XMP" && entry.short_name.starts_with("History")
+}
+
+/// Field of a structured `History` event, as read with `-struct`
+fn struct_field(event: &Value, name: &str) -> String {
+    match &event[name] {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// Decodes history fields back into events, i-th value of every field belongs to i-th event
+pub fn edit_history(entries: &[TagEntry]) -> Vec<HistoryEvent> {
+    let structured = entries
+        .iter()
+        .find(|e| e.table.0 == "XMP" && e.short_name == "History");
+    if let Some(EtVal::Array(events)) = structured.map(|e| &e.val) {
+        return events
+            .iter()
+            .map(|event| HistoryEvent {
+                when: struct_field(event, "When"),
+                action: struct_field(event, "Action"),
+                software_agent: struct_field(event, "SoftwareAgent"),
+                changed: struct_field(event, "Changed"),
+                parameters: struct_field(event, "Parameters"),
+            })
+            .collect();
+    }
+
+    let field = |short_name: &str| {
+        entries
+            .iter()
+            .find(|e| e.table.0 == "XMP" && e.short_name == short_name)
+            .map(|e| e.val.elements())
+            .unwrap_or_default()
+    };
+    let when = field("HistoryWhen");
+    let action = field("HistoryAction");
+    let software_agent = field("HistorySoftwareAgent");
+    let changed = field("HistoryChanged");
+    let parameters = field("HistoryParameters");
+
+    let len = [&when, &action, &software_agent, &changed, &parameters]
+        .iter()
+        .map(|v| v.len())
+        .max()
+        .unwrap_or_default();
+    let get = |v: &[String], i: usize| v.get(i).cloned().unwrap_or_default();
+    (0..len)
+        .map(|i| HistoryEvent {
+            when: get(&when, i),
+            action: get(&action, i),
+            software_agent: get(&software_agent, i),
+            changed: get(&changed, i),
+            parameters: get(&parameters, i),
+        })
+        .collect()
+}
+
+#[test]
+fn edit_history_test() {
+    let entry = |short_name: &str, val: Value| test_entry(short_name, "XMP::xmpMM", val, None);
+    let entries = vec![
+        entry("HistoryAction", serde_json::json!(["created", "saved"])),
+        entry(
+            "HistoryWhen",
+            serde_json::json!(["2024:01:01 10:00:00", "2024:01:02 11:00:00"]),
+        ),
+        entry("HistorySoftwareAgent", serde_json::json!("Adobe Photoshop")),
+    ];
+    assert!(is_history_tag(&entries[0]));
+
+    let history = edit_history(&entries);
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[0],
+        HistoryEvent {
+            when: String::from("2024:01:01 10:00:00"),
+            action: String::from("created"),
+            software_agent: String::from("Adobe Photoshop"),
+            ..Default::default()
+        }
+    );
+    assert_eq!(history[1].action, "saved");
+    assert!(history[1].software_agent.is_empty());
+}
+
+#[test]
+fn structured_edit_history_test() {
+    let entry = test_entry(
+        "History",
+        "XMP::xmpMM",
+        serde_json::json!([
+            {"Action": "created", "When": "2024:01:01 10:00:00"},
+            {"Action": "saved", "SoftwareAgent": "Adobe Photoshop", "Changed": "/"},
+        ]),
+        None,
+    );
+
+    let history = edit_history(&[entry]);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].when, "2024:01:01 10:00:00");
+    assert_eq!(history[1].software_agent, "Adobe Photoshop");
+    assert_eq!(history[1].changed, "/");
+    assert!(history[1].when.is_empty());
+}
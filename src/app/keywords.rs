@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::et_wrapper::TagEntry;
+
+/// Separator of Lightroom style hierarchical keywords, e.g. `Places|Europe|Paris`
+const SEPARATOR: char = '|';
+
+/// One node of keyword tree, rows go in tree order so they can be drawn as is
+pub struct KeywordRow {
+    pub depth: usize,
+    pub name: String,
+    /// Full keyword path, e.g. `Places|Europe`
+    pub path: String,
+}
+
+/// Builds keyword tree from XMP `HierarchicalSubject` values of a file
+pub fn keyword_tree(entries: &[TagEntry]) -> Vec<KeywordRow> {
+    // Sorted component lists come out parent first, followed by all of its children
+    let mut nodes = BTreeSet::new();
+    for entry in entries
+        .iter()
+        .filter(|e| e.table.0 == "XMP" && e.short_name == "HierarchicalSubject")
+    {
+        for keyword in entry.val.elements() {
+            let components = keyword
+                .split(SEPARATOR)
+                .map(|c| c.trim().to_owned())
+                .filter(|c| !c.is_empty())
+                .collect::<Vec<_>>();
+            for depth in 1..=components.len() {
+                nodes.insert(components[..depth].to_vec());
+            }
+        }
+    }
+    nodes
+        .into_iter()
+        .map(|components| KeywordRow {
+            depth: components.len() - 1,
+            name: components.last().cloned().unwrap_or_default(),
+            path: components.join(&SEPARATOR.to_string()),
+        })
+        .collect()
+}
+
+#[test]
+fn keyword_tree_test() {
+    let entry = test_entry(
+        "HierarchicalSubject",
+        "XMP::lr",
+        serde_json::json!([
+            "Places|Europe|Paris",
+            "Animals|Cat",
+            "Places|Asia",
+            "Places|Europe"
+        ]),
+        None,
+    );
+
+    let rows = keyword_tree(&[entry]);
+    let rows = rows
+        .iter()
+        .map(|r| (r.depth, r.name.as_str(), r.path.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            (0, "Animals", "Animals"),
+            (1, "Cat", "Animals|Cat"),
+            (0, "Places", "Places"),
+            (1, "Asia", "Places|Asia"),
+            (1, "Europe", "Places|Europe"),
+            (2, "Paris", "Places|Europe|Paris"),
+        ]
+    );
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+#[cfg(test)]
+use super::et_wrapper::test_entry;
+use super::et_wrapper::TagEntry;
+
+/// Terse lens names written by camera bodies, resolved to full product names.
+/// Entries from `lens_names` in config take priority over these
+const BUNDLED_LENS_NAMES: &[(&str, &str)] = &[
+    ("EF50mm f/1.8 STM", "Canon EF 50mm f/1.8 STM"),
+    ("EF50mm f/1.4 USM", "Canon EF 50mm f/1.4 USM"),
+    ("EF24-105mm f/4L IS USM", "Canon EF 24-105mm f/4L IS USM"),
+    (
+        "EF70-200mm f/2.8L IS II USM",
+        "Canon EF 70-200mm f/2.8L IS II USM",
+    ),
+    (
+        "EF-S18-55mm f/3.5-5.6 IS STM",
+        "Canon EF-S 18-55mm f/3.5-5.6 IS STM",
+    ),
+    ("RF24-105mm F4 L IS USM", "Canon RF 24-105mm F4 L IS USM"),
+    ("RF50mm F1.8 STM", "Canon RF 50mm F1.8 STM"),
+    (
+        "XF18-55mmF2.8-4 R LM OIS",
+        "Fujifilm XF 18-55mm F2.8-4 R LM OIS",
+    ),
+    ("XF35mmF1.4 R", "Fujifilm XF 35mm F1.4 R"),
+    ("XF23mmF2 R WR", "Fujifilm XF 23mm F2 R WR"),
+    (
+        "OLYMPUS M.12-40mm F2.8",
+        "Olympus M.Zuiko Digital ED 12-40mm F2.8 PRO",
+    ),
+    ("OLYMPUS M.25mm F1.8", "Olympus M.Zuiko Digital 25mm F1.8"),
+];
+
+/// Tags which lens lookup is made from, in order of preference
+const LENS_TAGS: [&str; 4] = ["LensID", "LensModel", "LensType", "Lens"];
+
+/// Camera and lens in plain words, derived from several tags
+pub struct LensSummary {
+    pub camera: Option<String>,
+    /// Lens name and tag it was derived from
+    pub lens: Option<(String, &'static str)>,
+}
+
+/// Whether details of this tag should show camera and lens summary
+pub fn is_lens_tag(entry: &TagEntry) -> bool {
+    ["Make", "Model", "LensInfo"].contains(&entry.short_name.as_str())
+        || LENS_TAGS.contains(&entry.short_name.as_str())
+}
+
+fn lookup(raw: &str, overrides: &HashMap<String, String>) -> Option<String> {
+    overrides.get(raw).cloned().or_else(|| {
+        BUNDLED_LENS_NAMES
+            .iter()
+            .find(|(k, _)| *k == raw)
+            .map(|(_, v)| v.to_string())
+    })
+}
+
+pub fn lens_summary(entries: &[TagEntry], overrides: &HashMap<String, String>) -> LensSummary {
+    let value = |short_name: &str| {
+        entries
+            .iter()
+            .find(|e| e.short_name == short_name)
+            .map(|e| e.val.to_string().trim().to_owned())
+            .filter(|v| !v.is_empty())
+    };
+
+    let camera = match (value("Make"), value("Model")) {
+        // Most makers repeat themselves in model name, e.g. `Canon` and `Canon EOS R5`
+        (Some(make), Some(model)) if model.to_lowercase().starts_with(&make.to_lowercase()) => {
+            Some(model)
+        }
+        (Some(make), Some(model)) => Some(format!("{make} {model}")),
+        (make, model) => make.or(model),
+    };
+
+    let lens = LENS_TAGS
+        .iter()
+        .find_map(|tag| {
+            value(tag)
+                .and_then(|raw| lookup(&raw, overrides))
+                .map(|name| (name, *tag))
+        })
+        .or_else(|| value("LensModel").map(|model| (model, "LensModel")))
+        .or_else(|| value("LensInfo").map(|info| (format!("Unknown lens, {info}"), "LensInfo")));
+
+    LensSummary { camera, lens }
+}
+
+#[test]
+fn lens_summary_test() {
+    let entry = |short_name: &str, val: &str| test_entry(short_name, "Exif::Main", val, None);
+    let mut overrides = HashMap::new();
+
+    let entries = vec![
+        entry("Make", "Canon"),
+        entry("Model", "Canon EOS R5"),
+        entry("LensModel", "RF24-105mm F4 L IS USM"),
+    ];
+    let summary = lens_summary(&entries, &overrides);
+    assert_eq!(summary.camera.as_deref(), Some("Canon EOS R5"));
+    assert_eq!(
+        summary.lens,
+        Some((String::from("Canon RF 24-105mm F4 L IS USM"), "LensModel"))
+    );
+
+    overrides.insert(
+        String::from("RF24-105mm F4 L IS USM"),
+        String::from("My walkaround lens"),
+    );
+    let summary = lens_summary(&entries, &overrides);
+    assert_eq!(
+        summary.lens,
+        Some((String::from("My walkaround lens"), "LensModel"))
+    );
+
+    let entries = vec![
+        entry("Make", "SONY"),
+        entry("Model", "ILCE-7M3"),
+        entry("LensInfo", "35mm f/1.4"),
+    ];
+    let summary = lens_summary(&entries, &overrides);
+    assert_eq!(summary.camera.as_deref(), Some("SONY ILCE-7M3"));
+    assert_eq!(
+        summary.lens,
+        Some((String::from("Unknown lens, 35mm f/1.4"), "LensInfo"))
+    );
+}
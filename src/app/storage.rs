@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{et_wrapper::TagEntryKey, CompareMode, DataDisplayMode, SortMode};
+
+/// How many recently opened paths are remembered for start screen
+const MAX_RECENTS: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub key: TagEntryKey,
+    pub name: String,
+}
+
+/// Data that should survive restarts, stored as json in user's data dir
+#[derive(Serialize, Deserialize, Default)]
+pub struct Storage {
+    #[serde(default)]
+    pub bookmarks: HashMap<PathBuf, Vec<Bookmark>>,
+    /// Most recently opened first
+    #[serde(default)]
+    pub recents: Vec<PathBuf>,
+}
+
+/// Everything needed to reopen files and get back to the same view
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub files: Vec<PathBuf>,
+    pub filter: String,
+    pub compare_mode: CompareMode,
+    pub current_file_index: usize,
+    pub cursor: usize,
+    pub data_display_mode: DataDisplayMode,
+    /// Missing in sessions saved before tags could be sorted
+    #[serde(default)]
+    pub sort_mode: SortMode,
+}
+
+/// Metadata of a file as it was when taken, kept to compare the file against later
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub source: PathBuf,
+    /// File's metadata in exiftool's json form
+    pub metadata: Value,
+}
+
+fn data_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "tool-exiftool").map(|dirs| dirs.data_dir().to_owned())
+}
+
+fn state_file_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("state.json"))
+}
+
+/// Json file of a named item in `kind` folder of data dir, e.g. `sessions`
+fn named_file_path(kind: &str, name: &str) -> std::io::Result<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("'{name}' is not a valid name"),
+        ));
+    }
+    data_dir()
+        .map(|dir| dir.join(kind).join(format!("{name}.json")))
+        .ok_or(std::io::ErrorKind::NotFound.into())
+}
+
+fn session_file_path(name: &str) -> std::io::Result<PathBuf> {
+    named_file_path("sessions", name)
+}
+
+fn snapshot_file_path(name: &str) -> std::io::Result<PathBuf> {
+    named_file_path("snapshots", name)
+}
+
+/// Files are tracked by canonical path, so that bookmarks don't depend on cwd
+fn file_key(file: &Path) -> PathBuf {
+    fs::canonicalize(file).unwrap_or_else(|_| file.to_owned())
+}
+
+impl Storage {
+    /// Will fall back to an empty storage if state file is missing or broken
+    pub fn load() -> Self {
+        state_file_path()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = state_file_path().ok_or(std::io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Moves given paths to the top of recents, forgetting the oldest ones over the limit
+    pub fn add_recents(&mut self, paths: &[PathBuf]) {
+        for path in paths.iter().rev().map(|p| file_key(p)) {
+            self.recents.retain(|p| p != &path);
+            self.recents.insert(0, path);
+        }
+        self.recents.truncate(MAX_RECENTS);
+    }
+
+    pub fn bookmarks(&self, file: &Path) -> &[Bookmark] {
+        self.bookmarks
+            .get(&file_key(file))
+            .map(|b| b.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if bookmark was added and `false` if it was removed
+    pub fn toggle_bookmark(&mut self, file: &Path, bookmark: Bookmark) -> bool {
+        let file_bookmarks = self.bookmarks.entry(file_key(file)).or_default();
+        if let Some(pos) = file_bookmarks.iter().position(|b| b.key == bookmark.key) {
+            file_bookmarks.remove(pos);
+            if file_bookmarks.is_empty() {
+                self.bookmarks.remove(&file_key(file));
+            }
+            false
+        } else {
+            file_bookmarks.push(bookmark);
+            true
+        }
+    }
+}
+
+impl Session {
+    pub fn save(&self, name: &str) -> std::io::Result<PathBuf> {
+        let path = session_file_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(name: &str) -> std::io::Result<Self> {
+        let data = fs::read(session_file_path(name)?).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to read session '{name}': {e}"))
+        })?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+}
+
+impl Snapshot {
+    pub fn save(&self, name: &str) -> std::io::Result<PathBuf> {
+        let path = snapshot_file_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(name: &str) -> std::io::Result<Self> {
+        let data = fs::read(snapshot_file_path(name)?).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to read snapshot '{name}': {e}"))
+        })?;
+        let snapshot: Self = serde_json::from_slice(&data)?;
+        if !snapshot.metadata.is_object() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Snapshot '{name}' holds no metadata"),
+            ));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn delete(name: &str) -> std::io::Result<()> {
+        fs::remove_file(snapshot_file_path(name)?)
+    }
+
+    /// Names of stored snapshots, sorted
+    pub fn list() -> Vec<String> {
+        let Some(dir) = data_dir().map(|dir| dir.join("snapshots")) else {
+            return Vec::new();
+        };
+        let mut names = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                (path.extension()? == "json")
+                    .then(|| path.file_stem()?.to_str().map(String::from))
+                    .flatten()
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+}
@@ -1,11 +1,18 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use app::{App, MainInput, Screen};
+use app::{
+    command::{self, Action},
+    config::ThemeSetting,
+    et_wrapper, App, BinarySaveDialog, MainInput, MainState, Pane, Screen,
+};
 use copypasta::ClipboardProvider;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
-        MouseEvent, MouseEventKind,
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
     },
     ExecutableCommand,
 };
@@ -14,72 +21,192 @@ use ratatui::DefaultTerminal;
 mod app;
 mod ui;
 
+const HSCROLL_JUMP: i8 = 20;
+/// Lines details pane scrolls by on <PGUP/PGDN>
+const DETAILS_PAGE: i16 = 10;
+/// How often loading screen checks on exiftool and redraws
+const LOADING_POLL: Duration = Duration::from_millis(100);
+/// Shortest allowed tick interval in milliseconds, so that a typo in config doesn't spin the CPU
+const MIN_TICK: u64 = 50;
+
 fn main() -> std::io::Result<()> {
-    let mut args = std::env::args();
-    let mut app = if args.len() > 2 {
-        App::new_multiple_files(args.skip(1).map(|s| PathBuf::from(s)).collect())
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let exiftool = match take_option(&mut args, "--exiftool-path") {
+        Some(path) => PathBuf::from(path.expect("You should provide a path to exiftool")),
+        None => PathBuf::from(et_wrapper::DEFAULT_EXIFTOOL),
+    };
+    let mut app = if args.first().is_some_and(|arg| arg == "--session") {
+        let name = args.get(1).expect("You should provide a session name");
+        App::restore_session(name, exiftool)?
+    } else if args.first().is_some_and(|arg| arg == "--from-file") {
+        let manifest = args.get(1).expect("You should provide a manifest file");
+        App::new_multiple_files(read_manifest(Path::new(manifest))?, exiftool)?
+    } else if args.is_empty() {
+        App::start_screen(exiftool)
     } else {
-        let input_path = PathBuf::from(&args.nth(1).expect("You should provide an image path"));
-        if input_path.is_dir() {
-            App::new_multiple_files(vec![input_path.to_owned()])
-        } else {
-            App::new(input_path)
+        App::open(args.into_iter().map(PathBuf::from).collect(), exiftool)?
+    };
+
+    app.main_state.light_theme = match app.main_state.config.theme {
+        ThemeSetting::Auto => {
+            ui::theme::query_light_background(ui::theme::QUERY_TIMEOUT).unwrap_or_default()
         }
-    }?;
+        ThemeSetting::Dark => false,
+        ThemeSetting::Light => true,
+    };
 
     std::io::stdout().execute(EnableMouseCapture).unwrap();
+    std::io::stdout().execute(EnableBracketedPaste).unwrap();
 
     let mut terminal = ratatui::init();
 
     let hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
         std::io::stdout().execute(DisableMouseCapture).unwrap();
+        std::io::stdout().execute(DisableBracketedPaste).unwrap();
         hook(info);
     }));
 
-    terminal.clear()?;
-    run_app(&mut app, terminal)?;
+    // Terminal is restored before any error gets printed
+    let result = terminal.clear().and_then(|()| run_app(&mut app, terminal));
     ratatui::restore();
     std::io::stdout().execute(DisableMouseCapture).unwrap();
-    Ok(())
+    std::io::stdout().execute(DisableBracketedPaste).unwrap();
+    if let Err(e) = app.main_state.save_display_prefs() {
+        eprintln!("Failed to save display settings: {e}");
+    }
+    result
+}
+
+/// Removes option and its value from anywhere in `args`, value is `None` if option is the last one
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<Option<String>> {
+    let pos = args.iter().position(|arg| arg == name)?;
+    args.remove(pos);
+    Some((pos < args.len()).then(|| args.remove(pos)))
+}
+
+/// Paths listed in a manifest file, relative ones are relative to the manifest's folder
+fn read_manifest(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let text = std::fs::read_to_string(path)?;
+    let paths = parse_manifest(&text, path.parent().unwrap_or(Path::new("")));
+    if paths.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} lists no paths", path.display()),
+        ));
+    }
+    Ok(paths)
+}
+
+/// One path per line, blank lines and lines starting with `#` are skipped
+fn parse_manifest(text: &str, base_dir: &Path) -> Vec<PathBuf> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base_dir.join(line))
+        .collect()
+}
+
+/// What main loop has to do after an event
+enum Handled {
+    Quit,
+    Redraw,
+    Unchanged,
 }
 
 fn run_app(app: &mut App, mut terminal: DefaultTerminal) -> std::io::Result<()> {
+    let mut redraw = true;
     loop {
-        terminal.draw(|frame| ui::ui(frame, app))?;
-        if handle_events(app)? {
-            break;
+        // Preview comes from exiftool running in background, details are redrawn once it's there
+        if app.main_state.show_details && app.main_state.poll_binary_preview() {
+            redraw = true;
+        }
+        let loading = matches!(app.screen, Screen::Loading);
+        if loading {
+            app.poll_loading();
+        }
+        if redraw || loading {
+            terminal.draw(|frame| ui::ui(frame, app))?;
+        }
+        let tick = if matches!(app.screen, Screen::Loading) {
+            LOADING_POLL
+        } else {
+            Duration::from_millis(app.main_state.config.tick_interval_ms.max(MIN_TICK))
+        };
+        if !event::poll(tick)? {
+            // Idle ticks only redraw when something has changed with time
+            redraw = app.main_state.tick();
+            continue;
+        }
+        match handle_events(app)? {
+            Handled::Quit => break,
+            Handled::Redraw => redraw = true,
+            Handled::Unchanged => redraw = false,
         }
     }
     Ok(())
 }
 
-fn handle_events(app: &mut App) -> std::io::Result<bool> {
-    match event::read()? {
+fn handle_events(app: &mut App) -> std::io::Result<Handled> {
+    let handled = match event::read()? {
         Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-            return Ok(handle_key_press_events(key_event, app));
+            if handle_key_press_events(key_event, app) {
+                Handled::Quit
+            } else {
+                Handled::Redraw
+            }
         }
         Event::Mouse(mouse_event) => {
-            handle_mouse_event(mouse_event, app);
+            if handle_mouse_event(mouse_event, app) {
+                Handled::Redraw
+            } else {
+                Handled::Unchanged
+            }
         }
-        _ => {}
+        // Terminals deliver dropped files as pasted paths
+        Event::Paste(text) if matches!(app.screen, Screen::Start) => {
+            app.paste_start_path(&text);
+            Handled::Redraw
+        }
+        Event::Paste(text) if matches!(app.screen, Screen::Main(MainInput::Filter)) => {
+            app.main_state.paste_filter(&text);
+            Handled::Redraw
+        }
+        _ => Handled::Redraw,
     };
-    Ok(false)
+    Ok(handled)
 }
 
-fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) {
+/// Tells whether screen has to be redrawn, moving mouse mostly doesn't change anything
+fn handle_mouse_event(mouse_event: MouseEvent, app: &mut App) -> bool {
     let state = &mut app.main_state;
-    match &app.screen {
-        Screen::Main(input) if matches!(input, MainInput::Main) => match mouse_event.kind {
+    if let Screen::Main(MainInput::Main) = &app.screen {
+        match mouse_event.kind {
             MouseEventKind::ScrollUp => {
                 state.scrollv_drag_cursor(-1);
+                state.hovered = None;
             }
             MouseEventKind::ScrollDown => {
                 state.scrollv_drag_cursor(1);
+                state.hovered = None;
+            }
+            MouseEventKind::Moved => {
+                return state.hover(mouse_event.column, mouse_event.row);
             }
             _ => {}
-        },
-        _ => {}
+        }
+    } else if mouse_event.kind == MouseEventKind::Moved {
+        return false;
+    }
+    true
+}
+
+/// Write actions go to confirmation, unless they were rejected right away
+fn write_confirm_or_main(state: &MainState) -> MainInput {
+    if state.pending_write.is_some() {
+        MainInput::WriteConfirm
+    } else {
+        MainInput::Main
     }
 }
 
@@ -96,34 +223,209 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
             KeyCode::Char('s') => {
                 state.data_display_mode.short = !state.data_display_mode.short;
             }
+            KeyCode::Char('n') if !state.search_query.is_empty() => {
+                state.search_step(true);
+            }
             KeyCode::Char('n') => {
                 state.data_display_mode.numerical = !state.data_display_mode.numerical;
             }
+            KeyCode::Char('o') => {
+                state.cycle_sort_mode();
+            }
+            KeyCode::Char('v') => {
+                *input = MainInput::ViewName;
+                state.view_name.clear();
+            }
+            KeyCode::Char('V') => {
+                state.remove_active_view();
+            }
+            KeyCode::Char(']') => {
+                state.switch_view(1);
+            }
+            KeyCode::Char('[') => {
+                state.switch_view(-1);
+            }
+            KeyCode::F(5) => {
+                state.log_msg = Some(match state.reload_files() {
+                    Ok(()) => Ok(String::from("Files were read again")),
+                    Err(e) => Err(format!("Failed to reload files: {e}")),
+                });
+            }
+            KeyCode::Char('K') => {
+                state.build_keyword_tree();
+                app.screen = Screen::Keywords;
+            }
+            KeyCode::Char('g') => {
+                state.build_gps_summary();
+                app.screen = Screen::Gps;
+            }
+            KeyCode::Char('J') => {
+                state.toggle_structured_xmp();
+            }
+            KeyCode::Char('a') => {
+                state.config.vertical_arrays = !state.config.vertical_arrays;
+            }
+            KeyCode::Char('z') => {
+                state.config.zebra_stripes = !state.config.zebra_stripes;
+            }
+            KeyCode::Char('I') => {
+                state.config.show_instance = !state.config.show_instance;
+            }
+            KeyCode::Char('B') => {
+                state.config.details_verbose = !state.config.details_verbose;
+            }
+            KeyCode::Char('T') => {
+                state.config.filter_matches_family = !state.config.filter_matches_family;
+                state.clamp_cursor();
+            }
+            KeyCode::Char('D') => {
+                state.config.details_at_bottom = !state.config.details_at_bottom;
+            }
+            KeyCode::Char('L') => {
+                state.cycle_compact_mode();
+            }
             KeyCode::Char('f') => {
                 *input = MainInput::Filter;
                 state.scroll_offset = (0, 0);
                 state.cursor = 0;
             }
+            KeyCode::Char('>') => {
+                state.jump_to_problem(true);
+            }
+            KeyCode::Char('<') => {
+                state.jump_to_problem(false);
+            }
+            KeyCode::Char(':') => {
+                *input = MainInput::Command;
+                state.command_input.clear();
+            }
+            KeyCode::Char('/') => {
+                *input = MainInput::Search;
+                state.open_search();
+            }
+            KeyCode::Char('N') if !state.search_query.is_empty() => {
+                state.search_step(false);
+            }
+            KeyCode::Char('m') => {
+                state.toggle_bookmark();
+            }
+            KeyCode::Char('*') => {
+                state.toggle_essential();
+            }
+            KeyCode::Char('+') => {
+                state.toggle_mark();
+            }
+            KeyCode::Char('-') if !state.marked.is_empty() => {
+                state.clear_marks();
+            }
+            KeyCode::Char('Q') => {
+                state.essentials_only = !state.essentials_only;
+                state.clamp_cursor();
+            }
+            KeyCode::Char('\'') => {
+                state.bookmarks_cursor = 0;
+                *input = MainInput::Bookmarks;
+            }
+            KeyCode::Char('S') => {
+                *input = MainInput::SessionSave;
+                state.session_name.clear();
+            }
+            KeyCode::Char('Z') => {
+                state.open_snapshots();
+                *input = MainInput::Snapshots;
+            }
+            KeyCode::Char('O') => {
+                state.extractions_cursor = 0;
+                *input = MainInput::Extractions;
+            }
+            KeyCode::Char('P') => {
+                state.request_strip();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Char('p') if state.is_multiple_files() => {
+                state.open_compare_picker();
+                *input = MainInput::ComparePicker;
+            }
+            KeyCode::Char('M') if state.is_multiple_files() => {
+                state.open_copy_tags_picker();
+                *input = MainInput::CopyTagsPicker;
+            }
+            KeyCode::Char('t') => {
+                *input = MainInput::TimeShift;
+                state.time_shift_input.clear();
+            }
+            KeyCode::Char('G') => {
+                *input = MainInput::GeotagTrack;
+                state.geotag_input.clear();
+            }
             KeyCode::Char('w') => {
                 state.selected_entry().inspect(|e| e.open_web_page());
             }
             KeyCode::Char('h') => {
                 app.screen = Screen::Help;
             }
-            KeyCode::Up => {
+            KeyCode::Right
+                if key_event.modifiers.contains(KeyModifiers::CONTROL) && state.show_details =>
+            {
+                state.focus_pane(Pane::Details);
+            }
+            KeyCode::Left
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && (state.show_details || state.focus == Pane::Details) =>
+            {
+                state.focus_pane(Pane::List);
+            }
+            KeyCode::Up | KeyCode::Char('k') if state.focus == Pane::Details => {
+                state.scroll_details(-1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if state.focus == Pane::Details => {
+                state.scroll_details(1);
+            }
+            KeyCode::PageUp if state.focus == Pane::Details => {
+                state.scroll_details(-DETAILS_PAGE);
+            }
+            KeyCode::PageDown if state.focus == Pane::Details => {
+                state.scroll_details(DETAILS_PAGE);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
                 state.scrollv(-1);
             }
-            KeyCode::Down => {
+            KeyCode::Down | KeyCode::Char('j') => {
                 state.scrollv(1);
             }
+            KeyCode::PageUp => {
+                state.scroll_page(false);
+            }
+            KeyCode::PageDown => {
+                state.scroll_page(true);
+            }
+            KeyCode::Home => {
+                state.scroll_to_end(false);
+            }
+            KeyCode::End => {
+                state.scroll_to_end(true);
+            }
+            KeyCode::Left if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                state.scrollh(-HSCROLL_JUMP);
+            }
+            KeyCode::Right if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                state.scrollh(HSCROLL_JUMP);
+            }
             KeyCode::Left => {
-                state.scrollh(-1);
+                state.scrollh_accelerated(-1);
             }
             KeyCode::Right => {
-                state.scrollh(1);
+                state.scrollh_accelerated(1);
             }
             KeyCode::Enter => {
                 state.show_details = !state.show_details;
+                state.focus_pane(Pane::List);
+            }
+            KeyCode::Esc if state.focus == Pane::Details => {
+                state.focus_pane(Pane::List);
+            }
+            KeyCode::Esc if !state.search_query.is_empty() => {
+                state.search_query.clear();
             }
             KeyCode::Esc if state.show_details => {
                 state.show_details = false;
@@ -150,6 +452,20 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
                     )));
                 }
             }
+            KeyCode::Char('$') if state.selected_entry().is_some() => {
+                *input = MainInput::CommandTemplates;
+            }
+            KeyCode::Char('H') => match state.selected_value_hex() {
+                Ok(hex) => {
+                    app.clipboard
+                        .set_contents(hex)
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(String::from(
+                        "Succesfully copied hex value to clipboard",
+                    )));
+                }
+                Err(e) => state.log_msg = Some(Err(e)),
+            },
             KeyCode::Char('C') => {
                 if let Some(entry) = state.selected_entry() {
                     app.clipboard
@@ -160,12 +476,40 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
                     )));
                 }
             }
+            KeyCode::Char('i') if state.compare_data.mode.is_off() && state.open_value_edit() => {
+                *input = MainInput::EditValue;
+            }
+            KeyCode::Char('A') => {
+                app.clipboard
+                    .set_contents(state.shown_tags_text())
+                    .expect("Failed to set clipboard contents!");
+                state.log_msg = Some(Ok(String::from(
+                    "Succesfully copied shown tags to clipboard",
+                )));
+            }
+            KeyCode::Char('!') => {
+                let all_files = state.compare_data.mode.is_on();
+                if let Some(text) = state.warnings_text(all_files) {
+                    app.clipboard
+                        .set_contents(text)
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(String::from(
+                        "Succesfully copied warnings and errors to clipboard",
+                    )));
+                } else {
+                    state.log_msg = Some(Err(String::from("No warnings or errors to copy!")));
+                }
+            }
             KeyCode::Char('b') => {
-                if state
+                if let Some(msg) = state.binary_unavailable() {
+                    state.log_msg = Some(Err(String::from(msg)));
+                } else if state
                     .selected_entry()
                     .is_some_and(|e| e.binary_size_kb.is_some())
                 {
-                    state.binary_save_dialog = Some(Default::default());
+                    state.fetch_binary_preview();
+                    state.binary_save_dialog =
+                        Some(BinarySaveDialog::new(state.selected_binary_type()));
                     *input = MainInput::BinarySaveDialog;
                 } else {
                     state.log_msg = Some(Err(String::from(
@@ -173,6 +517,9 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
                     )));
                 }
             }
+            KeyCode::Char('#') if state.open_hex_view() => {
+                *input = MainInput::HexView;
+            }
             KeyCode::Char('F') => {
                 if let Some(entry) = state.selected_entry() {
                     state.filter = format!("<<{}>>", entry.table_to_string());
@@ -181,52 +528,99 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
                 }
             }
             KeyCode::Tab if state.is_multiple_files() => {
-                state.current_file_index += 1;
-                if state.current_file_index >= state.et_data.len() {
-                    state.current_file_index = 0;
-                }
-                state.current_file = state.et_data[state.current_file_index].file_name.clone();
+                state.step_current_file(true);
             }
             KeyCode::BackTab if state.is_multiple_files() => {
-                if state.current_file_index == 0 {
-                    state.current_file_index = state.et_data.len() - 1;
-                } else {
-                    state.current_file_index -= 1;
-                }
-                state.current_file = state.et_data[state.current_file_index].file_name.clone();
+                state.step_current_file(false);
+            }
+            KeyCode::Char(')') if state.is_multiple_files() => {
+                state.step_to_file_with_tag(true);
+            }
+            KeyCode::Char('(') if state.is_multiple_files() => {
+                state.step_to_file_with_tag(false);
+            }
+            KeyCode::Char('W') if state.is_multiple_files() && state.compare_data.mode.is_off() => {
+                state.close_current_file();
             }
-            KeyCode::Char('W')
-                if state.is_multiple_files() && state.compare_data.mode.is_none() =>
+            KeyCode::Char('r') if state.is_multiple_files() && state.compare_data.mode.is_off() => {
+                state.toggle_reference();
+            }
+            KeyCode::Char('d')
+                if state.reference_index().is_some() && state.compare_data.mode.is_off() =>
             {
-                state.et_data.remove(state.current_file_index);
-                if state.current_file_index == state.et_data.len() {
-                    state.current_file_index -= 1;
-                }
-                state.current_file = state.et_data[state.current_file_index].file_name.clone();
+                state.reference_diff_only = !state.reference_diff_only;
+                state.clamp_cursor();
+            }
+            KeyCode::Char('U') if state.is_multiple_files() => {
+                state.build_diff_summary();
+                app.screen = Screen::DiffSummary;
+            }
+            KeyCode::Char('l') if state.is_multiple_files() => {
+                state.build_exposure_table();
+                app.screen = Screen::Exposures;
+            }
+            KeyCode::Char('R') if state.is_multiple_files() => {
+                state.build_tag_report();
+                app.screen = Screen::TagReport;
             }
             KeyCode::Char('c') => {
-                if state.compare_data.mode.is_some() {
-                    state.compare_data.mode = None;
-                } else {
-                    state.compare_data.mode = Some(false);
-                }
-                state.scroll_offset = (0, 0);
-                state.cursor = 0;
-                state.current_file_index = 0;
+                state.toggle_compare_mode();
+            }
+            KeyCode::Char('}') if state.compare_data.mode.is_on() => {
+                state.page_compare_columns(true);
             }
-            KeyCode::Char('d') if state.compare_data.mode.is_some() => {
-                state.compare_data.mode = Some(!state.compare_data.mode.unwrap());
+            KeyCode::Char('{') if state.compare_data.mode.is_on() => {
+                state.page_compare_columns(false);
+            }
+            KeyCode::Char('e') if state.compare_data.mode.is_off() => {
+                state.export_tags_json(None);
+            }
+            KeyCode::Char('e') => {
+                state.export_compare_csv(None);
+            }
+            KeyCode::Char('E') if state.compare_data.mode.is_on() => {
+                state.export_compare_xlsx(None);
+            }
+            KeyCode::Char('Y') if state.compare_data.mode.is_on() => {
+                app.clipboard
+                    .set_contents(state.compare_column_text())
+                    .expect("Failed to set clipboard contents!");
+                state.log_msg = Some(Ok(String::from(
+                    "Succesfully copied file column to clipboard",
+                )));
+            }
+            KeyCode::Char('=')
+                if state.compare_data.mode.is_on() && state.open_expected_value() =>
+            {
+                *input = MainInput::ExpectedValue;
+            }
+            KeyCode::Char('d') if state.compare_data.mode.is_on() => {
+                state.compare_data.mode = state.compare_data.mode.next_filter();
                 state.scroll_offset = (0, 0);
                 state.cursor = 0;
             }
             _ => {}
         },
         Screen::Main(input) if matches!(input, MainInput::Filter) => match key_event.code {
+            KeyCode::Char('v') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                match app.clipboard.get_contents() {
+                    Ok(text) => state.paste_filter(&text),
+                    Err(_) => state.log_msg = Some(Err(String::from("Failed to read clipboard!"))),
+                }
+            }
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.clipboard
+                    .set_contents(state.filter.clone())
+                    .expect("Failed to set clipboard contents!");
+                state.log_msg = Some(Ok(String::from("Succesfully copied filter to clipboard")));
+            }
             KeyCode::Char(ch) => {
                 state.filter.push(ch);
+                state.clamp_cursor();
             }
             KeyCode::Backspace => {
                 state.filter.pop();
+                state.clamp_cursor();
             }
             KeyCode::Enter => {
                 *input = MainInput::Main;
@@ -234,6 +628,7 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
             KeyCode::Esc => {
                 *input = MainInput::Main;
                 state.filter.clear();
+                state.clamp_cursor();
             }
             _ => {}
         },
@@ -262,15 +657,377 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
                     dialog.editing_fname = !dialog.editing_fname;
                 }
             }
+            KeyCode::Enter if state.try_save_binary().is_ok() => {
+                state.binary_save_dialog = None;
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.binary_save_dialog = None;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::Command) => match key_event.code {
+            KeyCode::Tab => {
+                state.complete_command();
+            }
+            KeyCode::Char(ch) => {
+                state.command_input.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.command_input.pop();
+            }
             KeyCode::Enter => {
-                if let Ok(_) = state.try_save_binary() {
-                    state.binary_save_dialog = None;
+                match command::parse(&state.command_input) {
+                    Ok(Action::Quit) => return true,
+                    Ok(action) => state.run_action(action),
+                    Err(e) => state.log_msg = Some(Err(e)),
+                }
+                state.command_input.clear();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.command_input.clear();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::EditValue) => match key_event.code {
+            KeyCode::Char(ch) => {
+                if let Some(dialog) = &mut state.value_edit_dialog {
+                    dialog.value.push(ch);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(dialog) = &mut state.value_edit_dialog {
+                    dialog.value.pop();
+                }
+            }
+            KeyCode::Enter => {
+                state.request_value_edit();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Esc => {
+                state.value_edit_dialog = None;
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::Search) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.search_query.push(ch);
+                state.update_search();
+            }
+            KeyCode::Backspace => {
+                state.search_query.pop();
+                state.update_search();
+            }
+            KeyCode::Enter => {
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.cancel_search();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::CommandTemplates) => {
+            if let KeyCode::Char(kind @ ('r' | 'w' | 'd')) = key_event.code {
+                if let Some(command) = state
+                    .selected_entry()
+                    .and_then(|e| e.command_template(kind, &state.current_file))
+                {
+                    app.clipboard
+                        .set_contents(command)
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(String::from(
+                        "Succesfully copied exiftool command to clipboard",
+                    )));
+                }
+            }
+            *input = MainInput::Main;
+        }
+        Screen::Main(input) if matches!(input, MainInput::Bookmarks) => match key_event.code {
+            KeyCode::Up => {
+                state.bookmarks_cursor = state.bookmarks_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.bookmarks_cursor = (state.bookmarks_cursor + 1)
+                    .min(state.current_bookmarks().len().saturating_sub(1));
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                state.delete_selected_bookmark();
+            }
+            KeyCode::Enter => {
+                state.jump_to_selected_bookmark();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc | KeyCode::Char('\'') => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::Snapshots) => match key_event.code {
+            KeyCode::Up => {
+                state.snapshots_cursor = state.snapshots_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.snapshots_cursor =
+                    (state.snapshots_cursor + 1).min(state.snapshot_names.len().saturating_sub(1));
+            }
+            KeyCode::Char('s') => {
+                *input = MainInput::SnapshotSave;
+                state.snapshot_name.clear();
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                state.delete_selected_snapshot();
+            }
+            KeyCode::Enter => {
+                state.load_selected_snapshot();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc | KeyCode::Char('Z') => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::Extractions) => match key_event.code {
+            KeyCode::Up => {
+                state.extractions_cursor = state.extractions_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.extractions_cursor =
+                    (state.extractions_cursor + 1).min(state.extractions.len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('o') => {
+                state.open_extraction_folder();
+            }
+            KeyCode::Char('r') => {
+                state.repeat_extraction();
+            }
+            KeyCode::Esc | KeyCode::Char('O') => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::HexView) => {
+            let Some(view) = &mut state.hex_view else {
+                *input = MainInput::Main;
+                return false;
+            };
+            if let Some(goto_input) = &mut view.goto_input {
+                match key_event.code {
+                    KeyCode::Char(ch) => goto_input.push(ch),
+                    KeyCode::Backspace => {
+                        goto_input.pop();
+                    }
+                    KeyCode::Enter => {
+                        if let Err(e) = view.confirm_goto() {
+                            state.log_msg = Some(Err(e));
+                        }
+                    }
+                    KeyCode::Esc => view.goto_input = None,
+                    _ => {}
+                }
+                return false;
+            }
+            match key_event.code {
+                KeyCode::Up => view.scroll_rows(-1),
+                KeyCode::Down => view.scroll_rows(1),
+                KeyCode::PageUp => view.scroll_pages(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') => view.scroll_pages(1),
+                KeyCode::Home => view.go_to(0),
+                KeyCode::End => view.go_to_end(),
+                KeyCode::Char('g') => view.goto_input = Some(String::new()),
+                KeyCode::Esc | KeyCode::Char('#') => {
+                    state.hex_view = None;
                     *input = MainInput::Main;
                 }
+                _ => {}
+            }
+        }
+        Screen::Main(input) if matches!(input, MainInput::BatchSummary) => match key_event.code {
+            KeyCode::Up => {
+                state.batch_scroll = state.batch_scroll.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.batch_scroll =
+                    (state.batch_scroll + 1).min(state.batch_results.len().saturating_sub(1));
             }
             KeyCode::Esc => {
+                state.batch_results.clear();
                 *input = MainInput::Main;
-                state.binary_save_dialog = None;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::SnapshotSave) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.snapshot_name.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.snapshot_name.pop();
+            }
+            KeyCode::Enter => {
+                state.save_snapshot();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.snapshot_name.clear();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::SessionSave) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.session_name.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.session_name.pop();
+            }
+            KeyCode::Enter => {
+                state.save_session();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.session_name.clear();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::CopyTagsPicker) => match key_event.code {
+            KeyCode::Up => {
+                state.copy_tags_cursor = state.copy_tags_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.copy_tags_cursor =
+                    (state.copy_tags_cursor + 1).min(state.et_data.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                state.pick_copy_tags_file(false);
+            }
+            KeyCode::Char('s') => {
+                state.pick_copy_tags_file(true);
+            }
+            KeyCode::Enter => {
+                state.request_copy_tags();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::ComparePicker) => match key_event.code {
+            KeyCode::Up => {
+                state.compare_picks_cursor = state.compare_picks_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.compare_picks_cursor =
+                    (state.compare_picks_cursor + 1).min(state.et_data.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                let picked = &mut state.compare_picks[state.compare_picks_cursor];
+                *picked = !*picked;
+            }
+            KeyCode::Char('a') => {
+                state.pick_all_compare_files();
+            }
+            KeyCode::Enter if state.apply_compare_picks() => {
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::WriteConfirm) => match key_event.code {
+            KeyCode::Tab if state.pending_write_is_scoped() => {
+                state.write_to_all = !state.write_to_all;
+            }
+            KeyCode::Char('y') => {
+                state.confirm_write();
+                *input = if state.batch_results.is_empty() {
+                    MainInput::Main
+                } else {
+                    MainInput::BatchSummary
+                };
+            }
+            KeyCode::Char('b') => {
+                state.overwrite_original = !state.overwrite_original;
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                state.pending_write = None;
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::TimeShift) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.time_shift_input.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.time_shift_input.pop();
+            }
+            KeyCode::Tab if state.is_multiple_files() => {
+                state.write_to_all = !state.write_to_all;
+            }
+            KeyCode::Enter => {
+                state.request_time_shift();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.time_shift_input.clear();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::ExpectedValue) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.expected_input.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.expected_input.pop();
+            }
+            KeyCode::Enter => {
+                state.set_expected_value();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::GeotagTrack) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.geotag_input.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.geotag_input.pop();
+            }
+            KeyCode::Enter => {
+                state.request_geotag();
+                *input = write_confirm_or_main(state);
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.geotag_input.clear();
+            }
+            _ => {}
+        },
+        Screen::Main(input) if matches!(input, MainInput::ViewName) => match key_event.code {
+            KeyCode::Char(ch) => {
+                state.view_name.push(ch);
+            }
+            KeyCode::Backspace => {
+                state.view_name.pop();
+            }
+            KeyCode::Enter => {
+                state.add_view();
+                *input = MainInput::Main;
+            }
+            KeyCode::Esc => {
+                *input = MainInput::Main;
+                state.view_name.clear();
             }
             _ => {}
         },
@@ -280,25 +1037,198 @@ fn handle_key_press_events(key_event: KeyEvent, app: &mut App) -> bool {
             }
             _ => {}
         },
-        Screen::MiltipleFilesStart => match key_event.code {
-            KeyCode::Char('q') => {
-                return true;
+        Screen::TagReport => match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.screen = Screen::Main(Default::default());
+            }
+            KeyCode::Up => {
+                state.tag_report_cursor = state.tag_report_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.tag_report_cursor =
+                    (state.tag_report_cursor + 1).min(state.tag_report.len().saturating_sub(1));
             }
-            KeyCode::Char('y') | KeyCode::Enter => {
-                state
-                    .read_multiple_files(true)
-                    .expect("Failed to read data with exiftool!");
+            KeyCode::Char('o') => {
+                state.cycle_tag_report_sort();
+            }
+            _ => {}
+        },
+        Screen::DiffSummary => match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
                 app.screen = Screen::Main(Default::default());
             }
-            KeyCode::Char('n') | KeyCode::Esc => {
-                state
-                    .read_multiple_files(false)
-                    .expect("Failed to read data with exiftool!");
+            KeyCode::Up => {
+                state.diff_summary_cursor = state.diff_summary_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.diff_summary_cursor =
+                    (state.diff_summary_cursor + 1).min(state.diff_summary.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                state.open_diff_summary_file();
                 app.screen = Screen::Main(Default::default());
             }
             _ => {}
         },
+        Screen::Exposures => match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.screen = Screen::Main(Default::default());
+            }
+            KeyCode::Up => {
+                state.exposure_cursor = state.exposure_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.exposure_cursor =
+                    (state.exposure_cursor + 1).min(state.exposure_rows.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                state.open_exposure_file();
+                app.screen = Screen::Main(Default::default());
+            }
+            KeyCode::Char('E') => {
+                state.export_exposure_csv();
+            }
+            _ => {}
+        },
+        Screen::Gps => match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.screen = Screen::Main(Default::default());
+            }
+            KeyCode::Up => {
+                state.gps_cursor = state.gps_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.gps_cursor =
+                    (state.gps_cursor + 1).min(state.gps.rows.len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('y' | 'Y') => {
+                if let Some(row) = state.gps.rows.get(state.gps_cursor) {
+                    let text = if key_event.code == KeyCode::Char('Y') {
+                        row.readable.clone()
+                    } else {
+                        row.decimal.clone()
+                    };
+                    app.clipboard
+                        .set_contents(text.clone())
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(format!("Copied '{text}' to clipboard")));
+                }
+            }
+            KeyCode::Char('l') => match state.gps.lat_long() {
+                Some(text) => {
+                    app.clipboard
+                        .set_contents(text.clone())
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(format!("Copied '{text}' to clipboard")));
+                }
+                None => state.log_msg = Some(Err(String::from("File has no GPS position!"))),
+            },
+            _ => {}
+        },
+        Screen::Keywords => match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.screen = Screen::Main(Default::default());
+            }
+            KeyCode::Up => {
+                state.keywords_cursor = state.keywords_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.keywords_cursor =
+                    (state.keywords_cursor + 1).min(state.keyword_rows.len().saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(row) = state.keyword_rows.get(state.keywords_cursor) {
+                    app.clipboard
+                        .set_contents(row.path.clone())
+                        .expect("Failed to set clipboard contents!");
+                    state.log_msg = Some(Ok(format!("Copied '{}' to clipboard", row.path)));
+                }
+            }
+            _ => {}
+        },
+        Screen::Start => match key_event.code {
+            KeyCode::Esc => {
+                return true;
+            }
+            KeyCode::Up => {
+                state.recents_cursor = state.recents_cursor.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                state.recents_cursor =
+                    (state.recents_cursor + 1).min(state.storage.recents.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                app.open_from_start();
+            }
+            KeyCode::Char('v') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                match app.clipboard.get_contents() {
+                    Ok(text) => app.paste_start_path(&text),
+                    Err(_) => state.log_msg = Some(Err(String::from("Failed to read clipboard!"))),
+                }
+            }
+            KeyCode::Backspace => {
+                state.open_path_input.pop();
+            }
+            KeyCode::Char(c) => {
+                state.open_path_input.push(c);
+            }
+            _ => {}
+        },
+        Screen::Loading => {
+            if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) {
+                app.cancel_loading();
+            }
+        }
+        Screen::MiltipleFilesStart => match key_event.code {
+            KeyCode::Char('q') => {
+                return true;
+            }
+            KeyCode::Char('y' | 'n' | 'Y' | 'N') | KeyCode::Enter | KeyCode::Esc => {
+                let (recursive, for_all) = match key_event.code {
+                    KeyCode::Char(ch) => (ch.eq_ignore_ascii_case(&'y'), ch.is_ascii_uppercase()),
+                    code => (code == KeyCode::Enter, false),
+                };
+                if state.decide_recursion(recursive, for_all) {
+                    match state.start_loading() {
+                        Ok(()) => app.screen = Screen::Loading,
+                        Err(e) => app.back_to_start(Err(format!("Failed to run exiftool: {e}"))),
+                    }
+                }
+            }
+            _ => {}
+        },
         _ => {}
     };
     false
 }
+
+#[test]
+fn take_option_test() {
+    let mut args = ["a.jpg", "--exiftool-path", "/opt/exiftool", "b.jpg"]
+        .map(String::from)
+        .to_vec();
+    assert_eq!(
+        take_option(&mut args, "--exiftool-path"),
+        Some(Some(String::from("/opt/exiftool")))
+    );
+    assert_eq!(args, ["a.jpg", "b.jpg"]);
+    assert_eq!(take_option(&mut args, "--exiftool-path"), None);
+
+    let mut args = ["--exiftool-path"].map(String::from).to_vec();
+    assert_eq!(take_option(&mut args, "--exiftool-path"), Some(None));
+    assert!(args.is_empty());
+}
+
+#[test]
+fn parse_manifest_test() {
+    let text = "# holiday picks\n\nIMG_001.jpg\n  raw/IMG_002.dng  \n/abs/IMG_003.jpg\n";
+    assert_eq!(
+        parse_manifest(text, Path::new("/photos")),
+        vec![
+            PathBuf::from("/photos/IMG_001.jpg"),
+            PathBuf::from("/photos/raw/IMG_002.dng"),
+            PathBuf::from("/abs/IMG_003.jpg"),
+        ]
+    );
+    assert!(parse_manifest("# nothing here\n", Path::new("")).is_empty());
+}
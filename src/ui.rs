@@ -3,13 +3,29 @@ use ratatui::{
     style::{Color, Style, Stylize},
     text::{Line, Span, Text},
     widgets::{
-        block::Title, Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation,
-        ScrollbarState, Wrap,
+        block::Title, Block, Borders, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Table, TableState, Wrap,
     },
     Frame,
 };
 
-use crate::app::{et_wrapper::TagEntry, App, BinarySaveDialog, MainInput, MainState, Screen};
+use crate::app::{
+    analysis,
+    et_wrapper::{detect_binary_type, filter_note, filter_text, write_command_line, TagEntry},
+    hexview::{self, HexView},
+    history, lens, App, BinarySaveDialog, CompareMode, MainInput, MainState, Pane, Screen,
+    SortMode, ValueEditDialog, WriteAction, WriteOutcome,
+};
+
+pub mod theme;
+
+fn theme(state: &MainState) -> &'static theme::Theme {
+    if state.light_theme {
+        &theme::LIGHT
+    } else {
+        &theme::DARK
+    }
+}
 
 pub fn ui(frame: &mut Frame, app: &mut App) {
     let outer_layout =
@@ -19,56 +35,156 @@ pub fn ui(frame: &mut Frame, app: &mut App) {
         Screen::Main(input) => {
             let outer_layout = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
                 .split(outer_layout[0]);
-            draw_filename(frame, app, outer_layout[0]);
             let mut main_layout = outer_layout[1];
-            if app.main_state.is_multiple_files() && app.main_state.compare_data.mode.is_none() {
+            if app.main_state.is_multiple_files() && app.main_state.compare_data.mode.is_off() {
                 let layout = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
                     .split(main_layout);
                 draw_tabs(frame, &app.main_state, layout[0]);
                 main_layout = layout[1];
             }
+            let compact = app.main_state.is_compact(frame.area().width);
             if app.main_state.show_details {
-                let layout = Layout::horizontal([
-                    Constraint::Fill(if app.main_state.compare_data.mode.is_some() {
+                let constraints = [
+                    Constraint::Fill(if app.main_state.compare_data.mode.is_on() {
                         3
                     } else {
                         2
                     }),
                     Constraint::Fill(1),
-                ])
-                .split(main_layout);
-                draw_details(frame, &mut app.main_state, layout[1]);
+                ];
+                let at_bottom = compact || app.main_state.config.details_at_bottom;
+                let layout = if at_bottom {
+                    Layout::vertical(constraints).split(main_layout)
+                } else {
+                    Layout::horizontal(constraints).split(main_layout)
+                };
+                let borders = if at_bottom {
+                    Borders::ALL
+                } else {
+                    Borders::TOP | Borders::RIGHT | Borders::BOTTOM
+                };
+                app.main_state.details_scroll_max =
+                    draw_details(frame, &app.main_state, layout[1], borders);
                 main_layout = layout[0];
             }
             if !app.main_state.filter.is_empty() || matches!(input, MainInput::Filter) {
                 let layout = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)])
                     .split(main_layout);
-                draw_filter(frame, &mut app.main_state, layout[0]);
+                draw_filter(frame, &app.main_state, layout[0]);
                 main_layout = layout[1];
             }
-            if app.main_state.is_multiple_files() && app.main_state.compare_data.mode.is_some() {
+            // Only plain tag list is hovered, others leave it empty
+            app.main_state.main_area = Rect::default();
+            if app.main_state.is_multiple_files() && app.main_state.compare_data.mode.is_on() {
                 draw_main_compare(frame, &mut app.main_state, main_layout);
+            } else if compact {
+                draw_main_compact(frame, &mut app.main_state, main_layout);
             } else {
                 draw_main(frame, &mut app.main_state, main_layout);
             }
+            // After main area, which decides shown compare columns
+            draw_filename(frame, app, outer_layout[0]);
             if let Some(dialog) = &mut app.main_state.binary_save_dialog {
                 let popup_layout = centered_rect(60, 8, frame.area());
                 draw_binary_save_dialog(frame, dialog, popup_layout);
             }
+            if let Some(dialog) = &app.main_state.value_edit_dialog {
+                let popup_layout = centered_rect(60, 6, frame.area());
+                draw_value_edit_dialog(frame, dialog, popup_layout);
+            }
+            if matches!(input, MainInput::WriteConfirm) {
+                draw_write_confirm(frame, &app.main_state, frame.area());
+            }
+            if matches!(input, MainInput::CopyTagsPicker) {
+                let height = app.main_state.et_data.len() as u16 + 2;
+                let popup_layout = centered_rect(60, height, frame.area());
+                draw_copy_tags_picker(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::ComparePicker) {
+                let height = app.main_state.et_data.len() as u16 + 2;
+                let popup_layout = centered_rect(60, height, frame.area());
+                draw_compare_picker(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::CommandTemplates) {
+                let popup_layout = centered_rect(70, 8, frame.area());
+                draw_command_templates(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::Extractions) {
+                let height = app.main_state.extractions.len().max(1) as u16 + 2;
+                let popup_layout = centered_rect(80, height, frame.area());
+                draw_extractions(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::HexView) {
+                let popup_layout =
+                    centered_rect(90, frame.area().height.saturating_sub(6), frame.area());
+                if let Some(view) = &mut app.main_state.hex_view {
+                    draw_hex_view(frame, view, popup_layout);
+                }
+            }
+            if matches!(input, MainInput::BatchSummary) {
+                let height = app.main_state.batch_results.len() as u16 + 2;
+                let popup_layout = centered_rect(80, height, frame.area());
+                draw_batch_summary(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::Snapshots) {
+                let height = app.main_state.snapshot_names.len().max(1) as u16 + 2;
+                let popup_layout = centered_rect(60, height, frame.area());
+                draw_snapshots(frame, &app.main_state, popup_layout);
+            }
+            if matches!(input, MainInput::Bookmarks) {
+                let height = app.main_state.current_bookmarks().len().max(1) as u16 + 2;
+                let popup_layout = centered_rect(60, height, frame.area());
+                draw_bookmarks(frame, &app.main_state, popup_layout);
+            }
+        }
+        Screen::Help => draw_help(frame, &app.main_state, outer_layout[0]),
+        Screen::MiltipleFilesStart => {
+            draw_multiple_files_start(frame, &app.main_state, outer_layout[0])
         }
-        Screen::Help => draw_help(frame, outer_layout[0]),
-        Screen::MiltipleFilesStart => draw_multiple_files_start(frame, outer_layout[0]),
+        Screen::Loading => draw_loading(frame, &app.main_state, outer_layout[0]),
+        Screen::Start => draw_start(frame, &app.main_state, outer_layout[0]),
+        Screen::TagReport => draw_tag_report(frame, &app.main_state, outer_layout[0]),
+        Screen::DiffSummary => draw_diff_summary(frame, &app.main_state, outer_layout[0]),
+        Screen::Exposures => draw_exposures(frame, &app.main_state, outer_layout[0]),
+        Screen::Keywords => draw_keywords(frame, &app.main_state, outer_layout[0]),
+        Screen::Gps => draw_gps(frame, &app.main_state, outer_layout[0]),
     }
 
     draw_hints(frame, app, outer_layout[1]);
 }
 
-fn draw_filter(frame: &mut Frame, state: &mut MainState, layout: Rect) {
+fn draw_filter(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let (matched, total) = if state.compare_data.mode.is_on() {
+        (
+            state.visible_compare_rows().count(),
+            state.compare_data.data.len(),
+        )
+    } else {
+        (
+            state.visible_entries().count(),
+            state.et_data[state.current_file_index].tag_entries.len(),
+        )
+    };
     let filter_block = Block::default()
         .borders(Borders::LEFT | Borders::TOP | Borders::RIGHT)
         .bold()
-        .title(" Filter ");
-    let par = Paragraph::new(state.filter.as_str()).block(filter_block);
+        .title(if state.config.filter_matches_family {
+            " Filter [Names, values, families] "
+        } else {
+            " Filter [Names, values] "
+        })
+        .title(
+            Title::from(format!(" {matched} of {total} tags match "))
+                .alignment(ratatui::layout::Alignment::Right),
+        );
+    let line = match filter_note(&state.filter.to_lowercase()) {
+        Some(note) => Line::from(vec![
+            state.filter.as_str().into(),
+            format!("  {note}").dark_gray(),
+        ]),
+        None => Line::from(state.filter.as_str()),
+    };
+    let par = Paragraph::new(line).block(filter_block);
     frame.render_widget(par, layout);
 }
 
@@ -78,69 +194,128 @@ fn draw_main(frame: &mut Frame, state: &mut MainState, layout: Rect) {
 
     let mut key_lines = vec![];
     let mut val_lines = vec![];
-    for (i, entry) in state.et_data[state.current_file_index]
-        .tag_entries
-        .iter()
-        .filter(|ee| state.filter.is_empty() || ee.check_filter(&state.filter))
-        .enumerate()
-    {
-        let mut style = if entry.short_name.to_lowercase().contains("warning") {
-            Style::default().fg(Color::LightYellow)
-        } else if entry.short_name.to_lowercase().contains("error") {
-            Style::default().fg(Color::Red)
-        } else {
-            Style::default()
-        };
-        let key_str = if state.data_display_mode.short {
-            entry.short_name.clone()
-        } else {
-            entry.name.clone()
-        };
-
-        let val_str = if let Some(kb_size) = entry.binary_size_kb {
-            style = style.fg(Color::LightGreen);
-            format!("{:.1}Kb binary data; Can be extracted", kb_size)
-        } else {
-            let num = &entry.num;
-            if state.data_display_mode.numerical && num.is_some() {
-                num.as_ref().unwrap().to_string()
-            } else {
-                entry.val.to_string()
-            }
-        };
+    let mut clipped_rows = vec![];
+    let mut entry_rows = vec![];
+    let mut cursor_row = 0;
+    // Search is what user is looking for right now, so it's highlighted instead of filter
+    let filter_text = filter_text(&state.search_query).or_else(|| filter_text(&state.filter));
+    let highlight = |text: String, base: Style| {
+        highlight_matches(
+            text,
+            filter_text.as_deref(),
+            base,
+            theme(state).filter_match,
+        )
+    };
+    for (i, entry) in state.visible_entries().enumerate() {
+        let (key_str, val_str, mut style) = entry_strings(state, entry);
+        let rows = state.entry_rows(entry);
         if i == state.cursor {
+            cursor_row = key_lines.len() + state.array_element().min(rows - 1);
+        }
+        if i == state.cursor && rows == 1 {
             style = style.patch(Style::default().black().on_white().bold());
         }
 
-        key_lines.push(
-            Line::from(cut_string(key_str, &inner_layout[0], state.scroll_offset.1)).style(style),
+        let key_str = cut_string(
+            key_str,
+            &inner_layout[0],
+            state.scroll_offset.1,
+            state.config.scroll_ellipsis,
         );
-        val_lines.push(
-            Line::from(cut_string(val_str, &inner_layout[1], state.scroll_offset.1)).style(style),
-        );
-    }
-    state.num_entries_shown = key_lines.len();
-    let num_entries_in_viewport = layout.height.saturating_sub(2) as usize;
-    let need_scrollbar = num_entries_in_viewport < state.num_entries_shown;
-
-    if state.cursor < state.scroll_offset.0 as usize {
-        state.scroll_offset.0 = state.cursor as u16;
-    } else if state.cursor >= state.scroll_offset.0 as usize + num_entries_in_viewport {
-        state.scroll_offset.0 = (state.cursor - num_entries_in_viewport + 1) as u16;
-    }
-    state.scroll_offset.0 = state
-        .scroll_offset
-        .0
-        .min(state.num_entries_shown.saturating_sub(5) as u16);
-
-    let key_block = Block::bordered().title(
-        if state.data_display_mode.short {
-            " Tag [Short] "
+        let mut key_style = Style::default();
+        // Colors telling something about the tag itself win over family one
+        if let Some(color) = family_color(state, entry) {
+            if i != state.cursor && style.fg.is_none() {
+                key_style = key_style.fg(color);
+            }
+        }
+        let mut key_line = highlight(key_str.clone(), key_style);
+        if state.config.leader_dots {
+            let free_space = (inner_layout[0].width.saturating_sub(2) as usize)
+                .saturating_sub(key_str.chars().count());
+            if free_space > 1 {
+                key_line.push_span(" ");
+                key_line.push_span(".".repeat(free_space - 1).dark_gray());
+            }
+        }
+        if rows == 1 {
+            key_lines.push(key_line.style(style));
+            clipped_rows.push(is_clipped(
+                &val_str,
+                &inner_layout[1],
+                state.scroll_offset.1,
+            ));
+            val_lines.push(
+                highlight(
+                    cut_string(
+                        val_str,
+                        &inner_layout[1],
+                        state.scroll_offset.1,
+                        state.config.scroll_ellipsis,
+                    ),
+                    Style::default(),
+                )
+                .style(style),
+            );
         } else {
-            " Tag [Detailed] "
+            // Tag name goes on the first row only, every element gets its own indexed row
+            let elements = entry
+                .display_val(state.data_display_mode.numerical)
+                .elements();
+            for (j, element) in elements.into_iter().enumerate() {
+                let mut row_style = style;
+                if key_lines.len() == cursor_row && i == state.cursor {
+                    row_style = style.patch(Style::default().black().on_white().bold());
+                }
+                key_lines.push(if j == 0 {
+                    key_line.clone().style(row_style)
+                } else {
+                    Line::from("").style(row_style)
+                });
+                let element = format!("[{j}] {element}");
+                clipped_rows.push(is_clipped(
+                    &element,
+                    &inner_layout[1],
+                    state.scroll_offset.1,
+                ));
+                val_lines.push(
+                    highlight(
+                        cut_string(
+                            element,
+                            &inner_layout[1],
+                            state.scroll_offset.1,
+                            state.config.scroll_ellipsis,
+                        ),
+                        Style::default(),
+                    )
+                    .style(row_style),
+                );
+            }
         }
-        .bold(),
+        entry_rows.push(rows);
+    }
+    state.num_entries_shown = entry_rows.len();
+    state.entry_rows_shown = entry_rows;
+    if state.num_entries_shown == 0 {
+        state.cursor = 0;
+        state.scroll_offset.0 = 0;
+    }
+    let num_rows = key_lines.len();
+    let num_entries_in_viewport = layout.height.saturating_sub(2) as usize;
+    state.rows_in_viewport = num_entries_in_viewport;
+    let need_scrollbar = num_entries_in_viewport < num_rows;
+
+    state.scroll_offset.0 = scroll_for_cursor(
+        state.scroll_offset.0,
+        cursor_row,
+        num_entries_in_viewport,
+        num_rows,
     );
+
+    let key_block = Block::bordered()
+        .title(tag_column_title(state, "Tag").bold())
+        .title_bottom(scroll_position(state).right_aligned());
     let val_block = Block::default()
         .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
         .title(
@@ -152,6 +327,10 @@ fn draw_main(frame: &mut Frame, state: &mut MainState, layout: Rect) {
             .bold(),
         );
 
+    let columns = [
+        key_block.inner(inner_layout[0]),
+        val_block.inner(inner_layout[1]),
+    ];
     let key_par = Paragraph::new(key_lines)
         .scroll(state.scroll_offset)
         .block(key_block);
@@ -161,9 +340,23 @@ fn draw_main(frame: &mut Frame, state: &mut MainState, layout: Rect) {
 
     frame.render_widget(key_par, inner_layout[0]);
     frame.render_widget(val_par, inner_layout[1]);
+    draw_zebra_stripes(frame, state, &columns, cursor_row, num_rows);
+    draw_clip_markers(frame, state, columns[1], &clipped_rows);
+    state.main_area = layout;
+    draw_hover(frame, state, &columns, cursor_row);
+
+    if state.num_entries_shown == 0 && !state.filter.is_empty() {
+        draw_empty_state(frame, format!("No tags match '{}'", state.filter), layout);
+    } else if state.num_entries_shown == 0 && state.reference_diff_only {
+        draw_empty_state(
+            frame,
+            "No differences from reference file".to_owned(),
+            layout,
+        );
+    }
 
     if need_scrollbar {
-        let mut sb_state = ScrollbarState::new(state.num_entries_shown).position(state.cursor);
+        let mut sb_state = ScrollbarState::new(num_rows).position(cursor_row);
         let sb = Scrollbar::new(ScrollbarOrientation::VerticalLeft)
             .track_style(Style::default().fg(Color::Blue))
             .thumb_style(Style::default().fg(Color::LightBlue));
@@ -171,6 +364,148 @@ fn draw_main(frame: &mut Frame, state: &mut MainState, layout: Rect) {
     }
 }
 
+/// Color of tag name by its family, when turned on in config
+fn family_color(state: &MainState, entry: &TagEntry) -> Option<Color> {
+    if !state.config.family_colors {
+        return None;
+    }
+    let family = &entry.table.0;
+    Some(
+        state
+            .config
+            .family_color_overrides
+            .get(family)
+            .and_then(|color| color.parse().ok())
+            .unwrap_or_else(|| theme(state).family_color(family)),
+    )
+}
+
+/// Short or detailed tag name, with instance qualifier unless it's turned off
+fn tag_label(state: &MainState, entry: &TagEntry) -> String {
+    let name = if state.data_display_mode.short {
+        &entry.short_name
+    } else {
+        &entry.name
+    };
+    if state.config.show_instance && !entry.instance.is_empty() {
+        format!("{}:{name}", entry.instance)
+    } else {
+        name.clone()
+    }
+}
+
+/// Tag name, value and style of a tag list row
+fn entry_strings(state: &MainState, entry: &TagEntry) -> (String, String, Style) {
+    let mut style = if entry.short_name.to_lowercase().contains("warning") {
+        Style::default().fg(theme(state).warning)
+    } else if entry.short_name.to_lowercase().contains("error") {
+        Style::default().fg(theme(state).error)
+    } else if state.changed_on_reload(entry) {
+        Style::default().fg(theme(state).changed_on_reload)
+    } else if state.differs_from_reference(entry) == Some(true) {
+        Style::default().fg(theme(state).reference_diff)
+    } else {
+        Style::default()
+    };
+    let mut key_str = tag_label(state, entry);
+    if state.is_essential(entry) {
+        key_str.insert_str(0, "★ ");
+    }
+    if state.is_marked(entry) {
+        key_str.insert_str(0, "+ ");
+    }
+
+    let val_str = if let Some(kb_size) = entry.binary_size_kb {
+        style = style.fg(theme(state).binary);
+        format!("{:.1}Kb binary data; Can be extracted", kb_size)
+    } else {
+        entry.display_value(state.data_display_mode.numerical)
+    };
+    (key_str, val_str, style)
+}
+
+/// Single column `tag: value` list with wrapped values, for narrow terminals
+fn draw_main_compact(frame: &mut Frame, state: &mut MainState, layout: Rect) {
+    // Borders are the same for every block, so inner area is known before block is built
+    let inner = Block::bordered().inner(layout);
+    let width = inner.width.max(1) as usize;
+
+    let rows = state
+        .visible_entries()
+        .map(|entry| {
+            let (key_str, val_str, style) = entry_strings(state, entry);
+            let chars = format!("{key_str}: {val_str}").chars().collect::<Vec<_>>();
+            let lines = chars
+                .chunks(width)
+                .map(|chunk| chunk.iter().collect::<String>())
+                .collect::<Vec<_>>();
+            (lines, style)
+        })
+        .collect::<Vec<_>>();
+    state.num_entries_shown = rows.len();
+    state.entry_rows_shown.clear();
+    if state.num_entries_shown == 0 {
+        state.cursor = 0;
+        state.scroll_offset.0 = 0;
+    }
+
+    // Rows take different number of lines, so first visible row is moved until cursor fits
+    let height = inner.height as usize;
+    let mut first = (state.scroll_offset.0 as usize).min(state.cursor);
+    while first < state.cursor
+        && rows[first..=state.cursor]
+            .iter()
+            .map(|(lines, _)| lines.len())
+            .sum::<usize>()
+            > height
+    {
+        first += 1;
+    }
+    state.scroll_offset.0 = first as u16;
+
+    let mut text = vec![];
+    for (i, (lines, mut style)) in rows.into_iter().enumerate().skip(first) {
+        if text.len() >= height {
+            break;
+        }
+        if i == state.cursor {
+            style = style.patch(Style::default().black().on_white().bold());
+        } else if state.config.zebra_stripes && i % 2 == 1 {
+            style = style.bg(theme(state).zebra_stripe);
+        }
+        text.extend(
+            lines
+                .into_iter()
+                .map(|line| Line::from(format!("{line:<width$}")).style(style)),
+        );
+    }
+    let block = Block::bordered()
+        .title(tag_column_title(state, "Tag: Value").bold())
+        .title_bottom(scroll_position(state).right_aligned());
+    frame.render_widget(Paragraph::new(text).block(block), layout);
+
+    if state.num_entries_shown == 0 && !state.filter.is_empty() {
+        draw_empty_state(frame, format!("No tags match '{}'", state.filter), layout);
+    } else if state.num_entries_shown == 0 && state.reference_diff_only {
+        draw_empty_state(
+            frame,
+            "No differences from reference file".to_owned(),
+            layout,
+        );
+    }
+}
+
+/// Vertical scroll which keeps cursor on screen without scrolling past the last entry
+fn scroll_for_cursor(scroll: u16, cursor: usize, viewport: usize, total: usize) -> u16 {
+    let mut scroll = (scroll as usize).min(total.saturating_sub(viewport));
+    if cursor < scroll {
+        scroll = cursor;
+    } else if cursor >= scroll + viewport {
+        scroll = (cursor + 1).saturating_sub(viewport);
+    }
+    scroll as u16
+}
+
 fn transpose2<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
     assert!(!v.is_empty());
     let len = v[0].len();
@@ -185,63 +520,59 @@ fn transpose2<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
         .collect()
 }
 
+/// Compare columns narrower than this are paged instead
+const MIN_COMPARE_COLUMN_WIDTH: usize = 16;
+
 fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
-    let only_diff = state.compare_data.mode.unwrap();
-    let small_parts_num = 1 + state.et_data.len() as u32 * 2;
+    // Only a window of file columns fits, it follows current file
+    let num_files = state.compare_data.files.len();
+    let window = num_files
+        .min(state.config.compare_max_columns.max(1))
+        .min((layout.width as usize / MIN_COMPARE_COLUMN_WIDTH).max(1));
+    let mut first = state.compare_first_column;
+    let current_column = state.compare_column().unwrap_or(first);
+    if current_column < first {
+        first = current_column;
+    } else if current_column >= first + window {
+        first = current_column + 1 - window;
+    }
+    first = first.min(num_files - window);
+    state.compare_first_column = first;
+    state.compare_columns_shown = window;
+    let shown_files = first..first + window;
+
+    let small_parts_num = 1 + window as u32 * 2;
     let mut constraints = vec![Constraint::Ratio(1, small_parts_num)];
-    for _ in 0..state.et_data.len() {
+    for _ in 0..window {
         constraints.push(Constraint::Ratio(2, small_parts_num));
     }
     let inner_layout = Layout::horizontal(constraints).split(layout);
     let mut key_lines = vec![];
     let mut val_lines = vec![];
 
-    let check_filter = |v: &Vec<Option<TagEntry>>| {
-        state.filter.is_empty()
-            || v.iter()
-                .any(|v| v.as_ref().is_some_and(|v| v.check_filter(&state.filter)))
-    };
-
-    let check_diff = |v: &Vec<Option<TagEntry>>| {
-        if !only_diff {
-            true
-        } else {
-            let first = &v[0];
-            !v.iter().all(|entry| {
-                (entry.is_none() && first.is_none())
-                    || entry
-                        .as_ref()
-                        .is_some_and(|e| first.as_ref().is_some_and(|f| e == f))
-            })
-        }
-    };
-
-    for (i, (k, vals)) in state
-        .compare_data
-        .data
-        .iter()
-        .filter(|(_, v)| check_filter(v) && check_diff(v))
-        .enumerate()
-    {
+    for (i, (k, vals)) in state.visible_compare_rows().enumerate() {
         let mut style = if k.short_name.to_lowercase().contains("warning") {
-            Style::default().fg(Color::LightYellow)
+            Style::default().fg(theme(state).warning)
         } else if k.short_name.to_lowercase().contains("error") {
-            Style::default().fg(Color::Red)
+            Style::default().fg(theme(state).error)
         } else {
             Style::default()
         };
-        let key_str = if state.data_display_mode.short {
-            k.short_name.clone()
-        } else {
-            k.name.clone()
+        let expected = state.compare_data.expected.get(&k.as_key());
+        let mut key_str = match expected {
+            Some(expected) => format!("{} [= {expected}]", tag_label(state, k)),
+            None => tag_label(state, k),
         };
+        if state.is_marked(k) {
+            key_str.insert_str(0, "+ ");
+        }
 
-        let val_strs = vals
+        let val_strs = vals[shown_files.clone()]
             .iter()
             .map(|v| {
                 if let Some(v) = v {
                     if let Some(kb_size) = v.binary_size_kb {
-                        style = style.fg(Color::LightGreen);
+                        style = style.fg(theme(state).binary);
                         format!("{:.1}Kb binary data; Can be extracted", kb_size)
                     } else {
                         let num = &v.num;
@@ -261,45 +592,73 @@ fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
             style = style.patch(Style::default().black().on_white().bold());
         }
 
+        let key_str = cut_string(
+            key_str,
+            &inner_layout[0],
+            state.scroll_offset.1,
+            state.config.scroll_ellipsis,
+        );
         key_lines.push(
-            Line::from(cut_string(key_str, &inner_layout[0], state.scroll_offset.1)).style(style),
+            match family_color(state, k) {
+                Some(color) if i != state.cursor && style.fg.is_none() => {
+                    Line::from(key_str.fg(color))
+                }
+                _ => Line::from(key_str),
+            }
+            .style(style),
         );
         val_lines.push(
             val_strs
                 .into_iter()
-                .map(|v| {
-                    Line::from(cut_string(v, &inner_layout[1], state.scroll_offset.1)).style(style)
+                .zip(&vals[shown_files.clone()])
+                .map(|(v, entry)| {
+                    // Cursor row keeps its own colors
+                    let style = match expected {
+                        Some(expected) if i != state.cursor => {
+                            style.fg(if analysis::matches_expected(entry.as_ref(), expected) {
+                                theme(state).expected_match
+                            } else {
+                                theme(state).expected_mismatch
+                            })
+                        }
+                        _ => style,
+                    };
+                    Line::from(cut_string(
+                        v,
+                        &inner_layout[1],
+                        state.scroll_offset.1,
+                        state.config.scroll_ellipsis,
+                    ))
+                    .style(style)
                 })
                 .collect::<Vec<_>>(),
         );
     }
     state.num_entries_shown = key_lines.len();
+    state.entry_rows_shown.clear();
+    if state.num_entries_shown == 0 {
+        state.cursor = 0;
+        state.scroll_offset.0 = 0;
+    }
     let num_entries_in_viewport = layout.height.saturating_sub(2) as usize;
+    state.rows_in_viewport = num_entries_in_viewport;
     let need_scrollbar = num_entries_in_viewport < state.num_entries_shown;
 
-    if state.cursor < state.scroll_offset.0 as usize {
-        state.scroll_offset.0 = state.cursor as u16;
-    } else if state.cursor >= state.scroll_offset.0 as usize + num_entries_in_viewport {
-        state.scroll_offset.0 = (state.cursor - num_entries_in_viewport + 1) as u16;
-    }
-    state.scroll_offset.0 = state
-        .scroll_offset
-        .0
-        .min(state.num_entries_shown.saturating_sub(5) as u16);
-
-    let key_block = Block::bordered().title(
-        if state.data_display_mode.short {
-            " Tag [Short] "
-        } else {
-            " Tag [Detailed] "
-        }
-        .bold(),
+    state.scroll_offset.0 = scroll_for_cursor(
+        state.scroll_offset.0,
+        state.cursor,
+        num_entries_in_viewport,
+        state.num_entries_shown,
     );
-    let val_blocks = state
-        .et_data
+
+    let key_block = Block::bordered()
+        .title(tag_column_title(state, "Tag").bold())
+        .title_bottom(scroll_position(state).right_aligned());
+    let val_blocks = state.compare_data.files[shown_files.clone()]
         .iter()
         .enumerate()
-        .map(|(col, et)| {
+        .map(|(i, &col)| {
+            let et = &state.et_data[col];
             Block::default()
                 .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
                 .title(
@@ -312,13 +671,16 @@ fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
                 )
                 .title_bottom({
                     let title_str = et.file_name.to_str().unwrap_or("[INVALID FILE NAME]");
-                    let mut res = if inner_layout[col + 1].width as usize + 2 >= title_str.len() {
+                    let label = &state.file_labels[col].path;
+                    let mut res = if inner_layout[i + 1].width as usize + 2 >= title_str.len() {
                         title_str.to_owned()
                     } else {
                         format!(
                             "*{}",
-                            &title_str
-                                [title_str.len() - inner_layout[col + 1].width as usize + 2..]
+                            last_chars(
+                                label,
+                                (inner_layout[i + 1].width as usize).saturating_sub(2)
+                            )
                         )
                     }
                     .bold();
@@ -330,11 +692,23 @@ fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
         })
         .collect::<Vec<_>>();
 
+    let mut columns = vec![key_block.inner(inner_layout[0])];
+    columns.extend(
+        val_blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| b.inner(inner_layout[i + 1])),
+    );
+
     let key_par = Paragraph::new(key_lines)
         .scroll(state.scroll_offset)
         .block(key_block);
 
-    let val_lines = transpose2(val_lines);
+    let val_lines = if val_lines.is_empty() {
+        vec![vec![]; window]
+    } else {
+        transpose2(val_lines)
+    };
 
     let val_pars = val_lines
         .into_iter()
@@ -346,6 +720,23 @@ fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
     for (i, par) in val_pars.enumerate() {
         frame.render_widget(par, inner_layout[i + 1]);
     }
+    draw_zebra_stripes(
+        frame,
+        state,
+        &columns,
+        state.cursor,
+        state.num_entries_shown,
+    );
+
+    if state.num_entries_shown == 0 {
+        if !state.filter.is_empty() {
+            draw_empty_state(frame, format!("No tags match '{}'", state.filter), layout);
+        } else if state.compare_data.mode == CompareMode::DiffOnly {
+            draw_empty_state(frame, String::from("Files have no differing tags"), layout);
+        } else if state.compare_data.mode == CompareMode::MatchesOnly {
+            draw_empty_state(frame, String::from("Files have no matching tags"), layout);
+        }
+    }
 
     if need_scrollbar {
         let mut sb_state = ScrollbarState::new(state.num_entries_shown).position(state.cursor);
@@ -356,23 +747,166 @@ fn draw_main_compare(frame: &mut Frame, state: &mut MainState, layout: Rect) {
     }
 }
 
+/// Patches background of odd rows, so that it spans whole column width
+fn draw_zebra_stripes(
+    frame: &mut Frame,
+    state: &MainState,
+    columns: &[Rect],
+    cursor_row: usize,
+    num_rows: usize,
+) {
+    if !state.config.zebra_stripes {
+        return;
+    }
+    for column in columns {
+        for dy in 0..column.height {
+            let row = state.scroll_offset.0 as usize + dy as usize;
+            if row % 2 == 1 && row != cursor_row && row < num_rows {
+                let row_area = Rect {
+                    y: column.y + dy,
+                    height: 1,
+                    ..*column
+                };
+                frame
+                    .buffer_mut()
+                    .set_style(row_area, Style::default().bg(theme(state).zebra_stripe));
+            }
+        }
+    }
+}
+
+/// Marks rows of a column which have more content to the right than fits, in the free cell at its edge
+fn draw_clip_markers(frame: &mut Frame, state: &MainState, column: Rect, clipped_rows: &[bool]) {
+    if column.width == 0 {
+        return;
+    }
+    for dy in 0..column.height {
+        let row = state.scroll_offset.0 as usize + dy as usize;
+        if clipped_rows.get(row).copied().unwrap_or_default() {
+            frame.buffer_mut()[(column.right() - 1, column.y + dy)]
+                .set_char('›')
+                .set_fg(Color::Yellow);
+        }
+    }
+}
+
+/// Patches background of rows of the entry under mouse pointer, apart from cursor row
+fn draw_hover(frame: &mut Frame, state: &MainState, columns: &[Rect], cursor_row: usize) {
+    let Some(hovered) = state.hovered else {
+        return;
+    };
+    let first_row = state.entry_rows_shown.iter().take(hovered).sum::<usize>();
+    let Some(rows) = state.entry_rows_shown.get(hovered) else {
+        return;
+    };
+    for column in columns {
+        for dy in 0..column.height {
+            let row = state.scroll_offset.0 as usize + dy as usize;
+            if (first_row..first_row + rows).contains(&row) && row != cursor_row {
+                let row_area = Rect {
+                    y: column.y + dy,
+                    height: 1,
+                    ..*column
+                };
+                frame
+                    .buffer_mut()
+                    .set_style(row_area, Style::default().bg(theme(state).hover));
+            }
+        }
+    }
+}
+
+/// Pager-like cursor position, e.g. ` 12/340 3% `
+fn scroll_position(state: &MainState) -> Line<'static> {
+    if state.num_entries_shown == 0 {
+        return Line::default();
+    }
+    let percent = (state.cursor + 1) * 100 / state.num_entries_shown;
+    Line::from(format!(
+        " {}/{} {}% ",
+        state.cursor + 1,
+        state.num_entries_shown,
+        percent
+    ))
+}
+
+fn draw_empty_state(frame: &mut Frame, msg: String, layout: Rect) {
+    let msg_layout = centered_rect(80, 1, layout);
+    frame.render_widget(Line::from(msg).yellow().bold().centered(), msg_layout);
+}
+
 fn draw_filename(frame: &mut Frame, app: &App, layout: Rect) {
-    let title = if app.main_state.compare_data.mode.is_some() {
-        "Compare Mode".to_owned()
+    let title = if app.main_state.compare_data.mode.is_on() {
+        match app.main_state.compare_data.mode {
+            CompareMode::DiffOnly => "Compare Mode [Only differences]",
+            CompareMode::MatchesOnly => "Compare Mode [Only matches]",
+            _ => "Compare Mode",
+        }
+        .to_owned()
     } else if let Some(file_name) = app.main_state.current_file.to_str() {
         if file_name.len() >= layout.width.saturating_sub(2) as usize {
-            "...".to_owned()
-                + &file_name[file_name
-                    .len()
-                    .saturating_sub(layout.width.saturating_sub(2) as usize)
-                    + 3..]
+            // Shortest unique part of path keeps same-named files distinguishable
+            let label = &app.main_state.file_labels[app.main_state.current_file_index].path;
+            let take = (layout.width.saturating_sub(2) as usize).saturating_sub(3);
+            "...".to_owned() + last_chars(label, take)
         } else {
             file_name.to_owned()
         }
     } else {
         "[INVALID FILE NAME]".to_owned()
     };
-    let block = Block::bordered().title(title).bold().black().on_white();
+    let mut block = Block::bordered().title(title).bold().black().on_white();
+    let state = &app.main_state;
+    if state.compare_data.mode.is_off() && state.et_data[state.current_file_index].snapshot {
+        block = block.title(" [Snapshot] ");
+    } else if state.compare_data.mode.is_off() && state.current_is_sidecar() {
+        block = block.title(" [Sidecar, metadata only] ");
+    }
+    let (present, scored) = state.file_labels[state.current_file_index].completeness;
+    if state.compare_data.mode.is_off() && scored > 0 {
+        block = block.title(
+            Title::from(format!(
+                " Completeness {present}/{scored} {}{} ",
+                "▮".repeat(present),
+                "▯".repeat(scored - present)
+            ))
+            .alignment(ratatui::layout::Alignment::Right),
+        );
+    }
+    let hidden_messages = state.hidden_messages();
+    if hidden_messages > 0 {
+        block = block.title(
+            Title::from(format!(
+                " ⚠ {hidden_messages} warning(s) hidden, <!> copies them "
+            ))
+            .alignment(ratatui::layout::Alignment::Right),
+        );
+    }
+    let compared = state.compare_data.files.len();
+    if state.compare_data.mode.is_on() && compared < state.et_data.len() {
+        block = block.title(format!(
+            " {compared} of {} files picked ",
+            state.et_data.len()
+        ));
+    }
+    if state.compare_data.mode.is_on() && state.compare_columns_shown < compared {
+        block = block.title(format!(
+            " files {}-{} of {compared} ",
+            state.compare_first_column + 1,
+            state.compare_first_column + state.compare_columns_shown,
+        ));
+    }
+    if let Some(view) = state.active_view.and_then(|i| state.views.get(i)) {
+        block = block.title(
+            Title::from(format!(
+                " View: {} ({}/{}) ",
+                view.name,
+                state.active_view.unwrap_or_default() + 1,
+                state.views.len()
+            ))
+            .alignment(ratatui::layout::Alignment::Right),
+        );
+    }
     frame.render_widget(block, layout);
 }
 
@@ -386,23 +920,261 @@ fn draw_hints(frame: &mut Frame, app: &mut App, layout: Rect) {
         }
     } else {
         match &app.screen {
-            Screen::Main(input) if matches!(input, MainInput::Main) => {
+            Screen::Main(MainInput::Main) => {
+                let mut controls = vec!["<h> - help  ".light_yellow(), "<q> - quit".red()];
+                let num_marked = app.main_state.marked.len();
+                if num_marked > 0 {
+                    controls.push(
+                        format!("  {num_marked} marked, exports and copies take only them  ")
+                            .cyan(),
+                    );
+                    controls.push("<-> - clear marks".into());
+                }
+                if !app.main_state.search_query.is_empty() {
+                    controls.push(format!("  searching '{}'  ", app.main_state.search_query).cyan());
+                    controls.push("<n/N> - next/previous match  <ESC> - stop".into());
+                }
                 vec![
                     Line::from("<↑/↓/←/→/WHEEL> - scroll  <f> - filter  <ENTER> - details"),
-                    Line::from(vec!["<h> - help  ".light_yellow(), "<q> - quit".red()]),
+                    Line::from(controls),
+                ]
+            }
+            Screen::Main(MainInput::Filter) => {
+                vec![
+                    Line::from(vec![
+                        "Filtering by tags and values.  ".cyan(),
+                        "<CTRL+C> - copy filter  <CTRL+V> - paste".into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - apply  ".green(), "<ESC> - discard".red()]),
+                ]
+            }
+            Screen::Main(MainInput::Command) => {
+                vec![
+                    Line::from(vec![
+                        ":".cyan(),
+                        app.main_state.command_input.as_str().into(),
+                    ]),
+                    Line::from(vec![
+                        "goto <n>, filter <expr>, sort <order>, export xlsx|csv|json [path], write <tag> [value], quit  "
+                            .dark_gray(),
+                        "<TAB> - complete  <ENTER> - run  ".green(),
+                        "<ESC> - discard".red(),
+                    ]),
+                ]
+            }
+            Screen::Main(MainInput::Search) => {
+                vec![
+                    Line::from(vec![
+                        "/".cyan(),
+                        app.main_state.search_query.as_str().into(),
+                    ]),
+                    Line::from(vec![
+                        "Cursor jumps to matching tags, others stay shown  ".dark_gray(),
+                        "<ENTER> - confirm  ".green(),
+                        "<ESC> - cancel".red(),
+                    ]),
+                ]
+            }
+            Screen::Main(MainInput::SessionSave) => {
+                vec![
+                    Line::from(vec![
+                        "Session name: ".cyan(),
+                        app.main_state.session_name.as_str().into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - save  ".green(), "<ESC> - discard".red()]),
+                ]
+            }
+            Screen::Main(MainInput::CopyTagsPicker) => {
+                vec![
+                    Line::from(vec![
+                        "<SPACE> - pick as target  ".cyan(),
+                        "<s> - pick as source  ".cyan(),
+                        "<↑/↓> - move".into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - copy  ".green(), "<ESC> - cancel".red()]),
+                ]
+            }
+            Screen::Main(MainInput::ComparePicker) => {
+                vec![
+                    Line::from(vec![
+                        "<SPACE> - pick  ".cyan(),
+                        "<a> - pick all/none  ".cyan(),
+                        "<↑/↓> - move".into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - compare  ".green(), "<ESC> - cancel".red()]),
+                ]
+            }
+            Screen::Main(MainInput::WriteConfirm) => {
+                let mut controls = vec!["<y> - run  ".green(), "<n/ESC> - cancel".red()];
+                if app.main_state.pending_write_is_scoped() {
+                    controls.push("  <TAB> - switch between current and all files".into());
+                }
+                vec![
+                    Line::from("Run shown exiftool command(s)?".cyan()),
+                    Line::from(controls),
+                ]
+            }
+            Screen::Main(MainInput::TimeShift) => {
+                let state = &app.main_state;
+                let target = if state.write_to_all {
+                    "all loaded files"
+                } else {
+                    "current file"
+                };
+                let mut controls = vec!["<ENTER> - apply  ".green(), "<ESC> - discard".red()];
+                if state.is_multiple_files() {
+                    controls.push("  <TAB> - switch between current and all files".into());
+                }
+                vec![
+                    Line::from(vec![
+                        format!("Shift dates of {target} by (e.g. +1:00:00, -0:0:1 0:00:00): ")
+                            .cyan(),
+                        state.time_shift_input.as_str().into(),
+                    ]),
+                    Line::from(controls),
                 ]
             }
-            Screen::Main(input) if matches!(input, MainInput::Filter) => {
+            Screen::Main(MainInput::ExpectedValue) => {
                 vec![
-                    Line::from("Filtering by tags and values.".cyan()),
+                    Line::from(vec![
+                        "Expected value of selected tag, files are checked against it: ".cyan(),
+                        app.main_state.expected_input.as_str().into(),
+                    ]),
+                    Line::from(vec![
+                        "<ENTER> - apply, empty clears  ".green(),
+                        "<ESC> - discard".red(),
+                    ]),
+                ]
+            }
+            Screen::Main(MainInput::GeotagTrack) => {
+                vec![
+                    Line::from(vec![
+                        "Geotag all loaded files from track (GPX/NMEA/KML): ".cyan(),
+                        app.main_state.geotag_input.as_str().into(),
+                    ]),
                     Line::from(vec!["<ENTER> - apply  ".green(), "<ESC> - discard".red()]),
                 ]
             }
+            Screen::Main(MainInput::ViewName) => {
+                vec![
+                    Line::from(vec![
+                        "Save current filter as view named: ".cyan(),
+                        app.main_state.view_name.as_str().into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - save  ".green(), "<ESC> - discard".red()]),
+                ]
+            }
+            Screen::Main(MainInput::CommandTemplates) => {
+                vec![Line::from(vec![
+                    "<r/w/d> - copy command  ".cyan(),
+                    "<any other key> - cancel".red(),
+                ])]
+            }
+            Screen::Main(MainInput::SnapshotSave) => {
+                vec![
+                    Line::from(vec![
+                        "Snapshot name: ".cyan(),
+                        app.main_state.snapshot_name.as_str().into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - save  ".green(), "<ESC> - discard".red()]),
+                ]
+            }
+            Screen::Main(MainInput::Extractions) => {
+                vec![
+                    Line::from("<↑/↓> - select  <o/ENTER> - open folder  <r> - extract again"),
+                    Line::from("<ESC> - close".red()),
+                ]
+            }
+            Screen::Main(MainInput::HexView) => match app
+                .main_state
+                .hex_view
+                .as_ref()
+                .and_then(|view| view.goto_input.as_deref())
+            {
+                Some(goto_input) => vec![
+                    Line::from(vec![
+                        "Go to offset (decimal, or hex with 0x): ".cyan(),
+                        goto_input.into(),
+                    ]),
+                    Line::from(vec!["<ENTER> - go  ".green(), "<ESC> - discard".red()]),
+                ],
+                None => vec![
+                    Line::from("<↑/↓> - scroll  <PGUP/PGDN> - page  <HOME/END> - start/end  <g> - go to offset"),
+                    Line::from("<ESC> - close".red()),
+                ],
+            },
+            Screen::Main(MainInput::BatchSummary) => {
+                vec![
+                    Line::from("<↑/↓> - scroll"),
+                    Line::from("<ESC> - close".red()),
+                ]
+            }
+            Screen::Main(MainInput::Snapshots) => {
+                vec![
+                    Line::from(
+                        "<↑/↓> - select  <ENTER> - compare with loaded files  <s> - snapshot current file  <d> - delete",
+                    ),
+                    Line::from("<ESC> - close".red()),
+                ]
+            }
+            Screen::Main(MainInput::Bookmarks) => {
+                vec![
+                    Line::from("<↑/↓> - select  <ENTER> - jump  <d> - delete bookmark"),
+                    Line::from("<ESC> - close".red()),
+                ]
+            }
             Screen::Help => {
                 vec![Line::from("<ENTER/ESC/q> - go back")]
             }
+            Screen::Loading => {
+                vec![Line::from("<ESC/q> - cancel")]
+            }
             Screen::MiltipleFilesStart => {
-                vec![Line::from("<q> - quit")]
+                vec![
+                    Line::from("<Y/N> - answer for all remaining folders".cyan()),
+                    Line::from("<q> - quit"),
+                ]
+            }
+            Screen::Start => {
+                vec![
+                    Line::from(
+                        "Type, paste or drop a path to open, or pick a recently opened one.".cyan(),
+                    ),
+                    Line::from(vec![
+                        "<↑/↓> - select  <CTRL+v> - paste  <ENTER> - open  ".green(),
+                        "<ESC> - quit".red(),
+                    ]),
+                ]
+            }
+            Screen::TagReport => {
+                vec![Line::from(
+                    "<↑/↓> - scroll  <o> - change sorting  <ESC/q> - go back",
+                )]
+            }
+            Screen::DiffSummary => {
+                vec![Line::from(
+                    "<↑/↓> - scroll  <ENTER> - open file  <ESC/q> - go back",
+                )]
+            }
+            Screen::Exposures => {
+                vec![Line::from(
+                    "<↑/↓> - scroll  <ENTER> - open file  <E> - export CSV to Downloads  <ESC/q> - go back",
+                )]
+            }
+            Screen::Keywords => {
+                vec![Line::from(
+                    "<↑/↓> - scroll  <ENTER/y> - copy keyword path  <ESC/q> - go back",
+                )]
+            }
+            Screen::Gps => {
+                vec![
+                    Line::from(vec![
+                        "<ENTER/y> - copy decimal  ".cyan(),
+                        "<Y> - copy readable  ".cyan(),
+                        "<l> - copy as lat,long".cyan(),
+                    ]),
+                    Line::from("<↑/↓> - move  <ESC/q> - go back"),
+                ]
             }
             _ => vec![],
         }
@@ -414,77 +1186,312 @@ fn draw_hints(frame: &mut Frame, app: &mut App, layout: Rect) {
     frame.render_widget(par, layout);
 }
 
-fn draw_details(frame: &mut Frame, state: &MainState, layout: Rect) {
+/// Returns how far the pane can be scrolled
+fn draw_details(frame: &mut Frame, state: &MainState, layout: Rect, borders: Borders) -> u16 {
     if let Some(entry) = state.selected_entry() {
-        let block = Block::default()
-            .borders(Borders::TOP | Borders::RIGHT | Borders::BOTTOM)
+        let mut block = Block::default()
+            .borders(borders)
             .title((" Details [".to_owned() + &entry.short_name + "] ").bold());
+        if state.focus == Pane::Details {
+            block = block.border_style(Style::default().cyan());
+        }
 
-        let mut data = vec![
-            Line::from("Detailed name: ".to_owned() + &entry.name),
-            Line::from(
+        let verbose = state.config.details_verbose;
+        let mut header = vec![Line::from("Detailed name: ".to_owned() + &entry.name)];
+        if verbose {
+            header.push(Line::from(
                 "Tag ID: ".to_owned()
                     + &if let Some(id) = entry.id {
                         id.to_string() + &format!(" ({:#X})", id)
                     } else {
                         "[Unknown]".to_owned()
                     },
-            ),
-            Line::from(vec![
+            ));
+            header.push(Line::from(vec![
                 Span::from("Tag family: "),
-                entry.table_to_string().into(),
+                match family_color(state, entry) {
+                    Some(color) => entry.table_to_string().fg(color),
+                    None => entry.table_to_string().into(),
+                },
                 " <F> - filter by tag family".yellow(),
-            ]),
-            Line::from({
-                let strval = entry.val.to_string();
-                if strval.len() > layout.width as usize * 5 {
-                    vec![
-                        Span::from("Value: "),
-                        strval.as_str()[..layout.width as usize * 3]
-                            .to_owned()
-                            .into(),
-                        "... value too long, press <x> to copy".yellow(),
-                    ]
-                } else {
-                    vec![Span::from("Value: "), strval.into()]
+            ]));
+        }
+
+        // Readable and numerical values side by side, whichever display mode is on.
+        // Compact details only keep the shown one
+        let inner = block.inner(layout);
+        let column_count = if verbose { 2 } else { 1 };
+        let column_width = inner.width.saturating_sub(column_count - 1) / column_count;
+        let column_area = Rect::new(0, 0, column_width, 0);
+        let numerical = state.data_display_mode.numerical;
+        let num = entry.num.as_ref().unwrap_or(&entry.val);
+        let columns = [
+            ("Readable", !numerical, entry.val.to_string(), 'x'),
+            ("Numerical", numerical, num.to_string(), 'X'),
+        ]
+        .into_iter()
+        .filter(|(_, shown, _, _)| verbose || *shown)
+        .map(|(label, shown, strval, copy_key)| {
+            let mut title = Line::from(label.bold().underlined());
+            if shown {
+                title.push_span(" (shown)".dark_gray());
+            }
+            vec![
+                title,
+                details_value_line(state, "", strval, Some(copy_key), column_area),
+            ]
+        })
+        .collect::<Vec<_>>();
+        let columns_height = columns
+            .iter()
+            .map(|lines| wrapped_height(lines, column_width))
+            .max()
+            .unwrap_or_default();
+
+        let mut data = Vec::new();
+        if verbose {
+            for (label, val) in [
+                ("Raw value: ", Some(&entry.val)),
+                ("Raw numerical value: ", entry.num.as_ref()),
+            ] {
+                if let Some(val) = val.filter(|val| val.raw() != val.to_string()) {
+                    data.push(details_value_line(state, label, val.raw(), None, inner));
                 }
-            }),
-            Line::from({
-                let strval = if let Some(num) = &entry.num {
-                    num.to_string()
-                } else {
-                    entry.val.to_string()
-                };
-                if strval.len() > layout.width as usize * 5 {
-                    vec![
-                        Span::from("Numerical value: "),
-                        strval.as_str()[..layout.width as usize * 3]
-                            .to_owned()
-                            .into(),
-                        "... value too long, press <X> to copy".yellow(),
-                    ]
-                } else {
-                    vec![Span::from("Numerical value: "), strval.into()]
+            }
+
+            let previous_values = state.previous_values(entry);
+            if !previous_values.is_empty() {
+                data.push(Line::default());
+                data.push(Line::from("Values before reloads, latest first:").bold());
+                for value in previous_values.iter().rev() {
+                    data.push(Line::from(format!("  {value}")));
                 }
-            }),
-        ];
+            }
 
-        if let Some(index) = &entry.index {
-            data.push(Line::from(format!("Index: {index}")));
+            if lens::is_lens_tag(entry) {
+                let entries = &state.et_data[state.current_file_index].tag_entries;
+                let summary = lens::lens_summary(entries, &state.config.lens_names);
+                data.push(Line::default());
+                if let Some(camera) = summary.camera {
+                    data.push(Line::from(vec!["Camera: ".bold(), camera.into()]));
+                }
+                if let Some((lens, source)) = summary.lens {
+                    data.push(Line::from(vec![
+                        "Lens: ".bold(),
+                        lens.into(),
+                        format!(" (from {source})").dark_gray(),
+                    ]));
+                }
+            }
+
+            if entry.val.is_structured() {
+                data.push(Line::default());
+                data.push(Line::from("Structure:").bold());
+                data.extend(entry.val.tree_lines().into_iter().map(Line::from));
+            }
+
+            if let Some(index) = &entry.index {
+                data.push(Line::from(format!("Index: {index}")));
+            }
+
+            if history::is_history_tag(entry) {
+                data.push(Line::default());
+                data.push(Line::from("Edit history:").bold());
+                let entries = &state.et_data[state.current_file_index].tag_entries;
+                for event in history::edit_history(entries) {
+                    let mut spans = vec![
+                        event.when.cyan(),
+                        "  ".into(),
+                        event.action.bold(),
+                        "  ".into(),
+                        event.software_agent.into(),
+                    ];
+                    if !event.changed.is_empty() {
+                        spans.push(format!(" [{}]", event.changed).dark_gray());
+                    }
+                    if !event.parameters.is_empty() {
+                        spans.push(format!(" {}", event.parameters).dark_gray());
+                    }
+                    data.push(Line::from(spans));
+                }
+            }
+
+            if let Some(preview) = state.binary_preview(entry) {
+                data.push(Line::default());
+                if preview.is_empty() {
+                    data.push(Line::from("Failed to extract binary data preview").red());
+                } else {
+                    if let Some((name, _)) = detect_binary_type(preview) {
+                        data.push(Line::from(vec![
+                            Span::from("Detected type: "),
+                            name.light_green(),
+                        ]));
+                    }
+                    data.push(Line::from(format!("First {} bytes:", preview.len())));
+                    data.extend(hex_dump(preview, 0).into_iter().map(Line::from));
+                }
+            } else if state.binary_preview_pending(entry) {
+                data.push(Line::default());
+                data.push(Line::from("Extracting binary data preview...").dark_gray());
+            }
         }
 
         data.push(Line::default());
         data.push(Line::from("<C> - copy entry to clipboard").yellow());
-        if let Some(_) = entry.binary_size_kb {
+        let binary_unavailable = state.binary_unavailable();
+        if let Some(msg) = binary_unavailable.filter(|_| entry.binary_size_kb.is_some()) {
+            data.push(Line::from(msg).dark_gray());
+        } else if entry.binary_size_kb.is_some() {
             data.push(Line::from("<b> - extract binary data").yellow());
         }
+        if (entry.binary_size_kb.is_some() && binary_unavailable.is_none())
+            || entry.integer_hex().is_some()
+        {
+            data.push(Line::from("<H> - copy value as hex").yellow());
+        }
+
+        // Pane scrolls as a whole, each part hides as many of its rows as went above the top
+        let heights = [
+            wrapped_height(&header, inner.width),
+            columns_height,
+            wrapped_height(&data, inner.width),
+        ];
+        let scroll_max = heights.iter().sum::<u16>().saturating_sub(inner.height);
+        let mut scroll = state.details_scroll.min(scroll_max);
+        let hidden = heights.map(|height| {
+            let hidden = scroll.min(height);
+            scroll -= hidden;
+            hidden
+        });
+        let details_layout = Layout::vertical([
+            Constraint::Length(heights[0] - hidden[0]),
+            Constraint::Length(heights[1] - hidden[1]),
+            Constraint::Fill(1),
+        ])
+        .split(inner);
+        if state.focus == Pane::Details {
+            block = block.title_bottom(
+                Line::from(" <↑/↓/PGUP/PGDN> - scroll  <ESC> - back to list ").right_aligned(),
+            );
+        } else if scroll_max > 0 {
+            block =
+                block.title_bottom(Line::from(" more below, <CTRL+→> - scroll ").right_aligned());
+        }
+        frame.render_widget(block, layout);
+        frame.render_widget(
+            Paragraph::new(header)
+                .wrap(Wrap::default())
+                .scroll((hidden[0], 0)),
+            details_layout[0],
+        );
+        let columns_layout = Layout::horizontal(vec![Constraint::Fill(1); column_count as usize])
+            .spacing(1)
+            .split(details_layout[1]);
+        for (lines, area) in columns.into_iter().zip(columns_layout.iter()) {
+            frame.render_widget(
+                Paragraph::new(lines)
+                    .wrap(Wrap::default())
+                    .scroll((hidden[1], 0)),
+                *area,
+            );
+        }
+        frame.render_widget(
+            Paragraph::new(data)
+                .wrap(Wrap::default())
+                .scroll((hidden[2], 0)),
+            details_layout[2],
+        );
+        scroll_max
+    } else {
+        0
+    }
+}
+
+/// Classic 8 bytes per line dump: offset, hex bytes and printable ascii
+fn hex_dump(bytes: &[u8], start_offset: usize) -> Vec<String> {
+    bytes
+        .chunks(8)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:04X}  {hex:<23}  {ascii}", start_offset + i * 8)
+        })
+        .collect()
+}
 
-        let par = Paragraph::new(data).block(block).wrap(Wrap::default());
+/// Rough number of rows lines take when wrapped, ignoring word boundaries
+fn wrapped_height(lines: &[Line], width: u16) -> u16 {
+    lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(width.max(1) as usize))
+        .sum::<usize>() as u16
+}
 
-        frame.render_widget(par, layout);
+/// Long values are cut to a preview, sizes are configured in pane widths
+fn details_value_line<'a>(
+    state: &MainState,
+    label: &'a str,
+    strval: String,
+    copy_key: Option<char>,
+    layout: Rect,
+) -> Line<'a> {
+    let width = layout.width as usize;
+    if strval.chars().count() > width * state.config.details_value_max_lines {
+        Line::from(vec![
+            Span::from(label),
+            strval
+                .chars()
+                .take(width * state.config.details_value_preview_lines)
+                .collect::<String>()
+                .into(),
+            match copy_key {
+                Some(copy_key) => format!("... value too long, press <{copy_key}> to copy"),
+                None => String::from("... value too long"),
+            }
+            .yellow(),
+        ])
+    } else {
+        Line::from(vec![Span::from(label), strval.into()])
     }
 }
 
+fn draw_value_edit_dialog(frame: &mut Frame, dialog: &ValueEditDialog, layout: Rect) {
+    let block = Block::bordered().title(
+        Title::from(format!(" Edit {} ", dialog.tag).bold())
+            .alignment(ratatui::layout::Alignment::Center),
+    );
+    let text = Text::from(vec![
+        Line::from(vec![dialog.value.as_str().into(), " ".on_white()]),
+        Line::from(format!("Was: {}", dialog.original)).dark_gray(),
+        Line::from(vec![
+            "<ENTER> - write, empty value removes the tag  ".green(),
+            "<ESC> - discard".red(),
+        ]),
+    ]);
+    frame.render_widget(Clear, layout);
+    frame.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .on_dark_gray(),
+        layout,
+    );
+}
+
 /// |- Main Title ------------|
 /// |- Fname ---------- Fext -|
 /// |            |            |
@@ -560,29 +1567,415 @@ fn draw_binary_save_dialog(frame: &mut Frame, state: &mut BinarySaveDialog, layo
     frame.render_widget(bot_par, layout[1]);
 }
 
-fn draw_help(frame: &mut Frame, layout: Rect) {
+/// Exiftool commands for selected tag, for running them by hand
+fn draw_command_templates(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(
+            Title::from(" Copy exiftool command ".bold())
+                .alignment(ratatui::layout::Alignment::Center),
+        )
+        .on_dark_gray();
+
+    let lines = state
+        .selected_entry()
+        .map(|entry| {
+            [('r', "read"), ('w', "write"), ('d', "delete")]
+                .into_iter()
+                .flat_map(|(kind, label)| {
+                    [
+                        Line::from(format!("<{kind}> - {label}")).bold(),
+                        Line::from(
+                            entry
+                                .command_template(kind, &state.current_file)
+                                .unwrap_or_default(),
+                        ),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_bookmarks(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(Title::from(" Bookmarks ".bold()).alignment(ratatui::layout::Alignment::Center))
+        .on_dark_gray();
+
+    let bookmarks = state.current_bookmarks();
+    let lines = if bookmarks.is_empty() {
+        vec![Line::from("No bookmarks for this file, press <m> on a tag to add one").yellow()]
+    } else {
+        bookmarks
+            .iter()
+            .enumerate()
+            .map(|(i, bookmark)| {
+                let line = Line::from(vec![
+                    bookmark.name.as_str().bold(),
+                    format!(" ({})", bookmark.key.short_name).into(),
+                ]);
+                if i == state.bookmarks_cursor {
+                    line.black().on_white()
+                } else {
+                    line
+                }
+            })
+            .collect()
+    };
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_extractions(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(
+            Title::from(" Extracted binary data ".bold())
+                .alignment(ratatui::layout::Alignment::Center),
+        )
+        .on_dark_gray();
+
+    let lines = if state.extractions.is_empty() {
+        vec![Line::from("Nothing extracted yet, press <b> on a binary tag to extract it").yellow()]
+    } else {
+        state
+            .extractions
+            .iter()
+            .enumerate()
+            .map(|(i, extraction)| {
+                let source = extraction
+                    .source
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                let line = Line::from(vec![
+                    extraction.entry.short_name.as_str().bold(),
+                    format!(" of {source} → ").into(),
+                    extraction.path.display().to_string().into(),
+                ]);
+                if i == state.extractions_cursor {
+                    line.black().on_white()
+                } else {
+                    line
+                }
+            })
+            .collect()
+    };
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_hex_view(frame: &mut Frame, view: &mut HexView, layout: Rect) {
+    let block = Block::bordered()
+        .title(
+            Title::from(format!(" {} ", view.title).bold())
+                .alignment(ratatui::layout::Alignment::Center),
+        )
+        .title_bottom(
+            Line::from(format!(
+                " {:#x} / {:#x} ",
+                view.offset,
+                view.len.saturating_sub(1)
+            ))
+            .right_aligned(),
+        )
+        .on_dark_gray();
+
+    view.page_rows = block.inner(layout).height.max(1) as u64;
+    let lines = view
+        .rows(view.page_rows)
+        .into_iter()
+        .map(|(offset, bytes)| Line::from(hexview::hex_row(offset, &bytes)))
+        .collect::<Vec<_>>();
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_batch_summary(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(Title::from(" Write results ".bold()).alignment(ratatui::layout::Alignment::Center))
+        .on_dark_gray();
+
+    let lines = state
+        .batch_results
+        .iter()
+        .skip(state.batch_scroll)
+        .map(|result| {
+            let file = result.file.display().to_string();
+            match result.outcome {
+                WriteOutcome::Updated => Line::from(vec!["✓ ".green(), file.into()]),
+                WriteOutcome::Unchanged => {
+                    Line::from(vec!["– ".into(), file.into(), " (unchanged)".into()]).gray()
+                }
+                WriteOutcome::Failed => Line::from(vec![
+                    "✗ ".red(),
+                    file.into(),
+                    format!(": {}", result.message).red(),
+                ]),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_snapshots(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(Title::from(" Snapshots ".bold()).alignment(ratatui::layout::Alignment::Center))
+        .on_dark_gray();
+
+    let lines = if state.snapshot_names.is_empty() {
+        vec![Line::from("No snapshots stored, press <s> to take one of current file").yellow()]
+    } else {
+        state
+            .snapshot_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let line = Line::from(name.as_str().bold());
+                if i == state.snapshots_cursor {
+                    line.black().on_white()
+                } else {
+                    line
+                }
+            })
+            .collect()
+    };
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+/// Preview of exiftool write commands with affected files, shown before running them
+fn draw_write_confirm(frame: &mut Frame, state: &MainState, area: Rect) {
+    const MAX_COMMANDS_SHOWN: usize = 8;
+
+    let title = match state.pending_write {
+        Some(WriteAction::Geotag(_)) => " Geotag ",
+        Some(WriteAction::TimeShift(_)) => " Shift dates ",
+        Some(WriteAction::Strip) => " Strip all metadata ",
+        Some(WriteAction::CopyTags { .. }) => " Copy tags ",
+        Some(WriteAction::SetTag { .. }) => " Write tag ",
+        None => return,
+    };
+    let block = Block::bordered()
+        .title(Title::from(title.bold()).alignment(ratatui::layout::Alignment::Center))
+        .on_red();
+
+    let commands = state.write_commands();
+    let num_files = commands.iter().map(|(_, files)| files.len()).sum::<usize>();
+    let mut lines = vec![Line::from(format!(
+        "Following command(s) will modify {num_files} file(s):"
+    ))
+    .bold()];
+    lines.extend(
+        commands
+            .iter()
+            .take(MAX_COMMANDS_SHOWN)
+            .map(|(args, files)| Line::from(write_command_line(args, files))),
+    );
+    if commands.len() > MAX_COMMANDS_SHOWN {
+        lines.push(Line::from(format!(
+            "...and {} more",
+            commands.len() - MAX_COMMANDS_SHOWN
+        )));
+    }
+    lines.push(if state.overwrite_original {
+        Line::from(vec![
+            "Files are overwritten, no _original backups are kept  ".italic(),
+            "<b> - keep backups".into(),
+        ])
+    } else {
+        Line::from(vec![
+            "exiftool keeps _original backups of modified files  ".italic(),
+            "<b> - overwrite originals instead".into(),
+        ])
+    });
+
+    let width = area.width * 80 / 100;
+    let height = lines
+        .iter()
+        .map(|line| {
+            (line.width() as u16)
+                .div_ceil(width.saturating_sub(2).max(1))
+                .max(1)
+        })
+        .sum::<u16>()
+        + 2;
+    let layout = centered_rect(80, height.min(area.height), area);
+    frame.render_widget(Clear, layout);
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap::default()).block(block),
+        layout,
+    );
+}
+
+fn draw_copy_tags_picker(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(Title::from(" Copy tags ".bold()).alignment(ratatui::layout::Alignment::Center))
+        .on_dark_gray();
+
+    let lines = state
+        .et_data
+        .iter()
+        .enumerate()
+        .map(|(i, et)| {
+            let mark = if i == state.copy_tags_source {
+                "[from] ".cyan().bold()
+            } else if state.copy_tags_targets[i] {
+                "[to]   ".green().bold()
+            } else {
+                "[ ]    ".into()
+            };
+            let line = Line::from(vec![mark, et.file_name.display().to_string().into()]);
+            if i == state.copy_tags_cursor {
+                line.black().on_white()
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+fn draw_compare_picker(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(
+            Title::from(" Files to compare ".bold()).alignment(ratatui::layout::Alignment::Center),
+        )
+        .on_dark_gray();
+
+    let lines = state
+        .et_data
+        .iter()
+        .zip(&state.compare_picks)
+        .enumerate()
+        .map(|(i, (et, &picked))| {
+            let mark = if picked {
+                "[x] ".green().bold()
+            } else {
+                "[ ] ".into()
+            };
+            let line = Line::from(vec![mark, et.file_name.display().to_string().into()]);
+            if i == state.compare_picks_cursor {
+                line.black().on_white()
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    frame.render_widget(Clear, layout);
+    frame.render_widget(Paragraph::new(lines).block(block), layout);
+}
+
+/// What tag list colors mean, taken from the theme in use
+fn color_legend(state: &MainState) -> Line<'static> {
+    let theme = theme(state);
+    Line::from(vec![
+        "Colors: ".bold(),
+        "warning".fg(theme.warning),
+        "  ".into(),
+        "error".fg(theme.error),
+        "  ".into(),
+        "extractable binary data".fg(theme.binary),
+        "  ".into(),
+        "differs from reference file".fg(theme.reference_diff),
+        "  ".into(),
+        "changed on reload".fg(theme.changed_on_reload),
+        "  ★ essential tag".into(),
+    ])
+    .centered()
+}
+
+fn draw_help(frame: &mut Frame, state: &MainState, layout: Rect) {
     let block = Block::bordered().title("Help");
 
     let lines = vec![
+        color_legend(state),
+        Line::default(),
         Line::from("General controls").bold().centered(),
         Line::from("<↑/↓/←/→/WHEEL/SPACE> - scroll      <f> - filter by tags/values"),
+        Line::from("<j/k> - move cursor down/up, vim style"),
+        Line::from("<PGUP/PGDN> - move cursor by a page     <HOME/END> - go to first/last tag"),
+        Line::from("</> - search, cursor jumps to matching tags  <n/N> - next/previous match,"),
+        Line::from("      <n> toggles numerical values again once search is stopped with <ESC>"),
+        Line::from("<SHIFT+←/→> - scroll horizontally faster, holding <←/→> also speeds up"),
         Line::from("<ENTER> - toggle show details       <s> - toggle show short tag names"),
         Line::from("<n> - toggle show numerical representation of tag values"),
+        Line::from("<o> - cycle tag order: as exiftool prints them, by name, by ID, by group and name"),
+        Line::from("<I> - show or hide instance qualifiers of tag names, e.g. `Copy1:`"),
+        Line::from("<z> - toggle alternating row backgrounds"),
+        Line::from("<a> - toggle showing array values vertically, one element per row"),
+        Line::from("<g> - GPS panel with position in decimal and DMS forms, ready for mapping tools"),
+        Line::from("<K> - browse hierarchical keywords of current file as a tree"),
+        Line::from("<J> - toggle reading XMP structures as nested values, files are read again"),
+        Line::from("<L> - switch compact layout between automatic, on and off"),
+        Line::from("<D> - toggle details pane placement between side and bottom"),
+        Line::from("<O> - binary data extracted in this session, to open its folder or extract again"),
+        Line::from("<Z> - snapshots: keep metadata of current file to compare it against later"),
+        Line::from("<B> - toggle details pane between compact (name and value) and verbose"),
+        Line::from("<T> - toggle whether filter also matches tag families"),
+        Line::from("Filter tokens :array, :binary, :empty - only tags with such values"),
+        Line::from("<CTRL+C/CTRL+V> while filtering - copy filter to clipboard, paste into filter"),
+        Line::from("Filter terms like ISO>1600 or FocalLength<=35 - only tags with numerical value in range"),
+        Line::from("<r> - mark current file as reference, tags differing from it are highlighted"),
+        Line::from("<d> - with reference file set, toggle showing only tags that differ from it"),
+        Line::from("<G> - geotag loaded files from a GPS track file, exiftool keeps _original backups"),
+        Line::from("<t> - shift all date/time tags of current or all loaded files by an offset"),
+        Line::from("<P> - strip all metadata from current or all loaded files"),
+        Line::from("<M> - copy all tags from one loaded file to others"),
+        Line::from("      writes to several files show per file results, <ESC> closes them"),
+        Line::from("<p> - pick which loaded files take part in compare mode"),
+        Line::from("<CTRL+→/←> - move focus to details pane and back, to scroll long details"),
         Line::from("<b> - save binary data from tag     <h> - show this text"),
-        Line::from("<q> - quit"),
+        Line::from("<#> - browse binary data of tag as hex and ASCII, <g> goes to an offset"),
+        Line::from("<q> - quit                          <:> - command line, e.g. `:42`"),
+        Line::from("      commands: goto <n>, filter <expr>, sort <order>, export xlsx|csv|json [path], write <tag> [value], quit"),
+        Line::from("<F5> - read files again, changed tags are highlighted for a few seconds"),
+        Line::from("<>>/<<> - jump to next/previous warning or error, filter stays as is"),
         Line::default(),
         Line::from("Extra controls").bold().centered(),
         Line::from(
             "<x> - copy tag value to clipboard   <X> - copy tag numerical value to clipboard",
         ),
         Line::from("<C> - copy all entry data to clipboard"),
+        Line::from("<i> - edit value of selected tag, exiftool writes it to current file after confirmation"),
+        Line::from("<A> - copy all shown tags as `name<TAB>value` lines, of active column in compare mode"),
+        Line::from("<$> - copy exiftool command to read, write or delete selected tag"),
+        Line::from("<H> - copy integer value, or first bytes of binary data, as hex"),
         Line::from("<F> - filter by current tag's group (family)"),
         Line::from("<w> - try to open a web page with this tag's family's information"),
+        Line::from("<m> - toggle bookmark for this tag   <'> - show bookmarks for this file"),
+        Line::from("<v> - save current filter as a named view  <[/]> - switch between views"),
+        Line::from("<V> - remove current view"),
+        Line::from("<S> - save session, it can be restored with `--session <name>`"),
         Line::default(),
         Line::from("Multiple files extra controls").bold().centered(),
         Line::from("<TAB> - next tab                    <SHIFT+TAB> - previous tab"),
+        Line::from("<)/(> - next/previous file where selected tag has a different value"),
         Line::from("<c> - toggle side-by-side compare mode"),
-        Line::from("<d> - while in side-by-side compare mode, show only lines that differ"),
+        Line::from("<R> - show report of how often each tag appears and varies across files"),
+        Line::from("<U> - show how many tags differ per file, to spot outliers in a batch"),
+        Line::from("<l> - exposure settings of all files in capture order, e.g. to review brackets"),
+        Line::from("<*> - add/remove selected tag to essentials (★)  <Q> - show only essentials"),
+        Line::from("<!> - copy all warnings/errors of current file, or of all files in compare mode"),
+        Line::from("<d> - while in side-by-side compare mode, cycle between showing all lines,"),
+        Line::from("      only lines that differ and only lines that match across all files"),
+        Line::from("<{/}> - while in compare mode, show previous/next page of file columns"),
+        Line::from("<E> - while in compare mode, export shown lines to a spreadsheet in Downloads"),
+        Line::from("<e> - export shown tags of current file to <file name>.tags.json in Downloads,"),
+        Line::from("      in compare mode export shown lines to compare.csv instead"),
+        Line::from("<Y> - while in compare mode, copy tags and values of selected file column"),
+        Line::from("<+> - mark selected tag, exports and copies take only marked tags  <-> - clear marks"),
+        Line::from("<=> - while in compare mode, type expected value of selected tag to check files against"),
         Line::default(),
         Line::from("You can still change tabs while in side-by-side compare mode;"),
         Line::from("this will control what details will be shown, what data will be copied, extracted etc."),
@@ -593,9 +1986,232 @@ fn draw_help(frame: &mut Frame, layout: Rect) {
     frame.render_widget(par, layout);
 }
 
-fn draw_multiple_files_start(frame: &mut Frame, layout: Rect) {
-    let main_line = Line::from("You provided one or more folders as input. Please choose if you want to read them recursively:").bold().centered();
-    let main_par = Paragraph::new(main_line).wrap(Wrap::default());
+fn draw_tag_report(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(" Tag frequency across files ".bold())
+        .title(
+            Title::from(format!(" Sorted by {} ", state.tag_report_sort.label()))
+                .alignment(ratatui::layout::Alignment::Right),
+        );
+    let num_files = state.et_data.len();
+    let rows = state.tag_report.iter().map(|freq| {
+        let style = if freq.files_with_tag == num_files && freq.distinct_values == 1 {
+            Style::default().dark_gray()
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            if state.data_display_mode.short {
+                freq.short_name.clone()
+            } else {
+                freq.name.clone()
+            },
+            freq.family.clone(),
+            format!("{}/{}", freq.files_with_tag, num_files),
+            freq.distinct_values.to_string(),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Length(12),
+            Constraint::Length(16),
+        ],
+    )
+    .header(Row::new(vec!["Tag", "Family", "Files", "Distinct values"]).bold())
+    .highlight_style(Style::default().black().on_white().bold())
+    .block(block);
+
+    let mut table_state = TableState::default().with_selected(Some(state.tag_report_cursor));
+    frame.render_stateful_widget(table, layout, &mut table_state);
+}
+
+fn draw_diff_summary(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered()
+        .title(" Differing tags per file ".bold())
+        .title(
+            Title::from(" Most different files first ")
+                .alignment(ratatui::layout::Alignment::Right),
+        );
+    let rows = state.diff_summary.iter().map(|summary| {
+        let row = Row::new(vec![
+            state.file_labels[summary.file_index].path.clone(),
+            if summary.file_index == state.compare_data.files[0] {
+                String::from("-")
+            } else {
+                summary.diffs_from_first.to_string()
+            },
+            format!("{:.1}", summary.mean_diffs),
+        ]);
+        if summary.file_index == state.compare_data.files[0] {
+            row.cyan()
+        } else {
+            row
+        }
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(18),
+            Constraint::Length(22),
+        ],
+    )
+    .header(Row::new(vec!["File", "Diffs from first", "Avg diffs from others"]).bold())
+    .highlight_style(Style::default().black().on_white().bold())
+    .block(block);
+
+    let mut table_state = TableState::default().with_selected(Some(state.diff_summary_cursor));
+    frame.render_stateful_widget(table, layout, &mut table_state);
+}
+
+fn draw_exposures(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered().title(" Exposure of files ".bold()).title(
+        Title::from(" In capture order, ↑/↓ mark changes from previous shot ")
+            .alignment(ratatui::layout::Alignment::Right),
+    );
+    let rows = state.exposure_rows.iter().enumerate().map(|(i, row)| {
+        let previous = i.checked_sub(1).map(|i| &state.exposure_rows[i]);
+        let mut cells = vec![
+            Cell::from(state.file_labels[row.file_index].path.clone()),
+            Cell::from(row.taken.clone().unwrap_or_default()),
+        ];
+        for (column, value) in row.values.iter().enumerate() {
+            let change = match (
+                previous.and_then(|p| p.numbers[column]),
+                row.numbers[column],
+            ) {
+                (Some(previous), Some(number)) if number > previous => " ↑".green(),
+                (Some(previous), Some(number)) if number < previous => " ↓".red(),
+                _ => "".into(),
+            };
+            cells.push(Cell::from(Line::from(vec![
+                value.clone().unwrap_or_default().into(),
+                change,
+            ])));
+        }
+        Row::new(cells)
+    });
+    let mut constraints = vec![Constraint::Fill(1), Constraint::Length(24)];
+    constraints.extend([Constraint::Length(16); analysis::EXPOSURE_COLUMNS.len()]);
+    let header = ["File", "Taken"]
+        .into_iter()
+        .chain(analysis::EXPOSURE_COLUMNS.iter().map(|(label, _)| *label));
+    let table = Table::new(rows, constraints)
+        .header(Row::new(header).bold())
+        .highlight_style(Style::default().black().on_white().bold())
+        .block(block);
+
+    let mut table_state = TableState::default().with_selected(Some(state.exposure_cursor));
+    frame.render_stateful_widget(table, layout, &mut table_state);
+}
+
+fn draw_start(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let layout = Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).split(layout);
+
+    let recents = &state.storage.recents;
+    if recents.is_empty() {
+        let par = Paragraph::new("Nothing was opened yet".dark_gray())
+            .block(Block::bordered().title(" Recently opened ".bold()));
+        frame.render_widget(par, layout[0]);
+    } else {
+        let rows = recents.iter().map(|path| {
+            let row = Row::new(vec![path.display().to_string()]);
+            if path.exists() {
+                row
+            } else {
+                row.dark_gray()
+            }
+        });
+        let table = Table::new(rows, [Constraint::Fill(1)])
+            .highlight_style(Style::default().black().on_white().bold())
+            .block(Block::bordered().title(" Recently opened ".bold()));
+        let selected = state
+            .open_path_input
+            .trim()
+            .is_empty()
+            .then_some(state.recents_cursor);
+        let mut table_state = TableState::default().with_selected(selected);
+        frame.render_stateful_widget(table, layout[0], &mut table_state);
+    }
+
+    let input = Paragraph::new(state.open_path_input.as_str())
+        .block(Block::bordered().title(" Open path ".bold()));
+    frame.render_widget(input, layout[1]);
+}
+
+fn draw_keywords(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let block = Block::bordered().title(" Hierarchical keywords ".bold());
+    if state.keyword_rows.is_empty() {
+        frame.render_widget(block, layout);
+        draw_empty_state(
+            frame,
+            "No hierarchical keywords in this file".to_owned(),
+            layout,
+        );
+        return;
+    }
+    let rows = state
+        .keyword_rows
+        .iter()
+        .map(|row| Row::new(vec![format!("{}{}", "  ".repeat(row.depth), row.name)]));
+    let table = Table::new(rows, [Constraint::Fill(1)])
+        .highlight_style(Style::default().black().on_white().bold())
+        .block(block);
+
+    let mut table_state = TableState::default().with_selected(Some(state.keywords_cursor));
+    frame.render_stateful_widget(table, layout, &mut table_state);
+}
+
+fn draw_gps(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let mut block = Block::bordered().title(" GPS ".bold());
+    if let Some(lat_long) = state.gps.lat_long() {
+        block = block.title(
+            Title::from(format!(" {lat_long} ")).alignment(ratatui::layout::Alignment::Right),
+        );
+    }
+    if state.gps.rows.is_empty() {
+        frame.render_widget(block, layout);
+        draw_empty_state(frame, "No GPS data in this file".to_owned(), layout);
+        return;
+    }
+    let rows = state.gps.rows.iter().map(|row| {
+        Row::new(vec![
+            row.label.to_owned(),
+            row.readable.clone(),
+            row.decimal.clone(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(vec!["", "Readable", "Decimal"]).bold())
+    .highlight_style(Style::default().black().on_white().bold())
+    .block(block);
+
+    let mut table_state = TableState::default().with_selected(Some(state.gps_cursor));
+    frame.render_stateful_widget(table, layout, &mut table_state);
+}
+
+fn draw_multiple_files_start(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let folder = state
+        .recursion_prompts
+        .first()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let main_lines = vec![
+        Line::from(format!("Folder '{folder}' contains subfolders. Please choose if you want to read it recursively:")).bold().centered(),
+        Line::from(format!("{} folder(s) with subfolders left", state.recursion_prompts.len())).centered(),
+    ];
+    let main_par = Paragraph::new(main_lines).wrap(Wrap::default());
 
     let vertical_layout = Layout::vertical([
         Constraint::Max(5),
@@ -607,16 +2223,40 @@ fn draw_multiple_files_start(frame: &mut Frame, layout: Rect) {
     .split(layout);
 
     frame.render_widget(main_par, vertical_layout[0]);
+    let (flat, recursive) = state.recursion_file_counts;
     frame.render_widget(
-        Line::from("<y/ENTER>   YES").on_green().bold().centered(),
+        Line::from(format!("<y/ENTER>   YES, {recursive} file(s)"))
+            .on_green()
+            .bold()
+            .centered(),
         vertical_layout[1],
     );
     frame.render_widget(
-        Line::from("<n/ESC>     NO").on_red().bold().centered(),
+        Line::from(format!("<n/ESC>     NO, {flat} file(s)"))
+            .on_red()
+            .bold()
+            .centered(),
         vertical_layout[2],
     );
 }
 
+fn draw_loading(frame: &mut Frame, state: &MainState, layout: Rect) {
+    let Some(loading) = &state.loading else {
+        return;
+    };
+    let line = Line::from(format!(
+        "Reading metadata of {} path(s) with exiftool... {}s",
+        loading.num_paths,
+        loading.started.elapsed().as_secs()
+    ))
+    .bold()
+    .centered();
+    let vertical_layout = Layout::vertical([Constraint::Length(1)])
+        .flex(ratatui::layout::Flex::Center)
+        .split(layout);
+    frame.render_widget(line, vertical_layout[0]);
+}
+
 fn draw_tabs(frame: &mut Frame, state: &MainState, layout: Rect) {
     let num_files = state.et_data.len();
     let tab_len = (layout.width as f32 * 0.95 / num_files as f32) as u16;
@@ -634,15 +2274,15 @@ fn draw_tabs(frame: &mut Frame, state: &MainState, layout: Rect) {
     let tab_width = tab_layout[0].width;
     let take_text = tab_width.saturating_sub(4) as usize;
 
-    for (i, et) in state.et_data.iter().enumerate() {
-        let fname = et
-            .file_name
-            .to_str()
-            .expect("File path contains bad unicode");
-        let text = &fname[fname.len().saturating_sub(take_text + 1)..];
+    for (i, label) in state.file_labels.iter().enumerate() {
+        let text = last_chars(&label.path, take_text + 1);
         let mut line = Line::from(vec![
             "|".red().bold(),
-            "*".yellow(),
+            if state.reference_index() == Some(i) {
+                "R".magenta().bold()
+            } else {
+                "*".fg(completeness_color(label.completeness))
+            },
             text.into(),
             "|".red().bold(),
         ]);
@@ -653,23 +2293,120 @@ fn draw_tabs(frame: &mut Frame, state: &MainState, layout: Rect) {
     }
 }
 
-fn cut_string(mut s: String, target: &Rect, x_offset: u16) -> String {
-    if x_offset as usize >= s.len() && !s.is_empty() {
-        return ".".repeat((x_offset + 3) as usize).to_owned();
+/// Tab marker color, from green for complete metadata to red for less than half of it
+fn completeness_color((present, scored): (usize, usize)) -> Color {
+    if present == scored {
+        Color::Green
+    } else if present * 2 >= scored {
+        Color::Yellow
+    } else {
+        Color::Red
     }
-    if s.len().saturating_sub(x_offset as usize) >= (target.width - 2) as usize {
-        s.truncate((x_offset + target.width.saturating_sub(5)) as usize);
-        s += "...";
+}
+
+/// Last `n` characters of `s`, paths may have multi-byte characters anywhere
+fn last_chars(s: &str, n: usize) -> &str {
+    let start = s
+        .char_indices()
+        .rev()
+        .take(n)
+        .last()
+        .map_or(s.len(), |(i, _)| i);
+    &s[start..]
+}
+
+/// Part of `s` visible at horizontal scroll `x_offset`, padded for `Paragraph::scroll`.
+/// `...` marks content clipped on the right and, with `left_ellipsis`, on the left.
+/// Values scrolled past their end are blank
+fn cut_string(s: String, target: &Rect, x_offset: u16, left_ellipsis: bool) -> String {
+    let x = x_offset as usize;
+    let width = target.width.saturating_sub(2) as usize;
+    let chars = s.chars().skip(x).collect::<Vec<_>>();
+    if chars.is_empty() {
+        return String::new();
+    }
+    let mut visible = if chars.len() > width {
+        chars[..width.saturating_sub(3)]
+            .iter()
+            .chain(['.'; 3].iter())
+            .collect::<String>()
+    } else {
+        chars.iter().collect::<String>()
+    };
+    if x != 0 && left_ellipsis {
+        let dots = visible.chars().count().saturating_sub(1).min(3);
+        visible = ".".repeat(dots) + &visible.chars().skip(dots).collect::<String>();
     }
-    if x_offset != 0 {
-        let mid = (x_offset + 3) as usize;
-        if mid >= s.len() {
-            s = ".".repeat(mid);
+    " ".repeat(x) + &visible
+}
+
+/// Title of tag name column, telling name form and tag order
+fn tag_column_title(state: &MainState, name: &str) -> String {
+    let form = if state.data_display_mode.short {
+        "Short"
+    } else {
+        "Detailed"
+    };
+    match state.sort_mode {
+        SortMode::None => format!(" {name} [{form}] "),
+        mode => format!(" {name} [{form}, by {}] ", mode.label()),
+    }
+}
+
+/// Line of `text` with every case-insensitive occurrence of `term` on `background`, rest of it in `base` style
+fn highlight_matches(
+    text: String,
+    term: Option<&str>,
+    base: Style,
+    background: Color,
+) -> Line<'static> {
+    let ranges = term
+        .map(|term| match_ranges(&text, term))
+        .unwrap_or_default();
+    if ranges.is_empty() {
+        return Line::from(Span::styled(text, base));
+    }
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for (from, to) in ranges {
+        spans.push(Span::styled(text[end..from].to_owned(), base));
+        spans.push(Span::styled(
+            text[from..to].to_owned(),
+            base.fg(Color::Black).bg(background),
+        ));
+        end = to;
+    }
+    spans.push(Span::styled(text[end..].to_owned(), base));
+    Line::from(spans)
+}
+
+/// Byte ranges of non-overlapping case-insensitive occurrences of lowercase `term` in `text`
+fn match_ranges(text: &str, term: &str) -> Vec<(usize, usize)> {
+    let chars = text.char_indices().collect::<Vec<_>>();
+    let term = term.chars().collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while !term.is_empty() && i + term.len() <= chars.len() {
+        let is_match = chars[i..i + term.len()]
+            .iter()
+            .zip(&term)
+            .all(|((_, c), t)| c.to_lowercase().eq(t.to_lowercase()));
+        if is_match {
+            let end = chars
+                .get(i + term.len())
+                .map_or(text.len(), |(pos, _)| *pos);
+            ranges.push((chars[i].0, end));
+            i += term.len();
         } else {
-            s = ".".repeat(x_offset as usize + 3) + s.split_at(mid).1;
+            i += 1;
         }
     }
-    s
+    ranges
+}
+
+/// Whether `cut_string` has to cut the end of `s` off
+fn is_clipped(s: &str, target: &Rect, x_offset: u16) -> bool {
+    s.chars().count() > x_offset as usize + target.width.saturating_sub(2) as usize
 }
 
 fn centered_rect(percent_x: u16, size_y: u16, r: Rect) -> Rect {
@@ -691,6 +2428,82 @@ fn centered_rect(percent_x: u16, size_y: u16, r: Rect) -> Rect {
 #[test]
 fn cut_test() {
     let s = String::from("1234567890123");
-    let s = cut_string(s, &Rect::new(0, 0, 12, 7), 1);
+    let s = cut_string(s, &Rect::new(0, 0, 12, 7), 1, true);
     println!("s: {s}");
 }
+
+#[test]
+fn scroll_for_cursor_test() {
+    // Short list fits entirely, no scrolling
+    assert_eq!(scroll_for_cursor(3, 6, 10, 7), 0);
+    // Cursor at the end of a long list is the last visible row
+    assert_eq!(scroll_for_cursor(0, 19, 10, 20), 10);
+    // Moving up past the top scrolls with the cursor
+    assert_eq!(scroll_for_cursor(10, 4, 10, 20), 4);
+    // Scroll left after the list shrank is pulled back
+    assert_eq!(scroll_for_cursor(15, 2, 10, 12), 2);
+}
+
+#[test]
+fn last_chars_test() {
+    assert_eq!(last_chars("Фото/IMG_001.jpg", 13), "о/IMG_001.jpg");
+    assert_eq!(last_chars("Фото", 10), "Фото");
+    assert_eq!(last_chars("Фото", 0), "");
+}
+
+#[test]
+fn cut_string_scroll_test() {
+    let target = Rect::new(0, 0, 12, 7);
+    assert_eq!(cut_string("short".to_owned(), &target, 0, true), "short");
+    assert_eq!(cut_string("short".to_owned(), &target, 8, true), "");
+    assert_eq!(cut_string("short".to_owned(), &target, 2, true), "  ..t");
+    assert_eq!(
+        cut_string("1234567890123".to_owned(), &target, 0, true),
+        "1234567..."
+    );
+    assert_eq!(
+        cut_string("1234567890123".to_owned(), &target, 1, true),
+        " ...5678..."
+    );
+    assert_eq!(
+        cut_string("1234567890123".to_owned(), &target, 1, false),
+        " 2345678..."
+    );
+    assert!(is_clipped("1234567890123", &target, 0));
+    assert!(is_clipped("1234567890123", &target, 2));
+    assert!(!is_clipped("1234567890123", &target, 3));
+    assert!(!is_clipped("short", &target, 0));
+}
+
+#[test]
+fn match_ranges_test() {
+    assert_eq!(match_ranges("Canon EOS R5", "eos"), vec![(6, 9)]);
+    assert_eq!(match_ranges("aAaa", "aa"), vec![(0, 2), (2, 4)]);
+    assert_eq!(match_ranges("Île-de-France", "île"), vec![(0, 4)]);
+    assert!(match_ranges("Canon", "").is_empty());
+    assert!(match_ranges("Canon", "nikon").is_empty());
+
+    let line = highlight_matches(
+        String::from("ISO 100"),
+        Some("100"),
+        Style::default(),
+        Color::Yellow,
+    );
+    assert_eq!(line.spans.len(), 3);
+    assert_eq!(line.spans[1].content, "100");
+    assert_eq!(line.spans[1].style.bg, Some(Color::Yellow));
+    assert_eq!(
+        highlight_matches(String::from("ISO"), None, Style::default(), Color::Yellow)
+            .spans
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn hex_dump_test() {
+    let lines = hex_dump(b"\xFF\xD8\xFF\xE0\x00\x10JFIF", 0);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "0000  FF D8 FF E0 00 10 4A 46  ......JF");
+    assert_eq!(lines[1], "0008  49 46                    IF");
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use ratatui::style::Color;
+
+/// Colors which have to differ between dark and light terminal backgrounds
+pub struct Theme {
+    pub zebra_stripe: Color,
+    pub hover: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub binary: Color,
+    pub reference_diff: Color,
+    pub changed_on_reload: Color,
+    pub expected_match: Color,
+    pub expected_mismatch: Color,
+    /// Background of text matching filter
+    pub filter_match: Color,
+    /// Tag families are spread over these
+    pub family_palette: [Color; 8],
+}
+
+impl Theme {
+    /// Color of tag family, derived from its name so it stays the same between runs
+    pub fn family_color(&self, family: &str) -> Color {
+        // FNV-1a, std hasher output isn't guaranteed to be stable
+        let hash = family.bytes().fold(0x811c9dc5u32, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        });
+        self.family_palette[hash as usize % self.family_palette.len()]
+    }
+}
+
+pub const DARK: Theme = Theme {
+    zebra_stripe: Color::Indexed(236),
+    hover: Color::Indexed(239),
+    warning: Color::LightYellow,
+    error: Color::Red,
+    binary: Color::LightGreen,
+    reference_diff: Color::LightMagenta,
+    changed_on_reload: Color::LightCyan,
+    expected_match: Color::Green,
+    expected_mismatch: Color::LightRed,
+    filter_match: Color::Yellow,
+    family_palette: [
+        Color::Indexed(110),
+        Color::Indexed(114),
+        Color::Indexed(180),
+        Color::Indexed(174),
+        Color::Indexed(139),
+        Color::Indexed(109),
+        Color::Indexed(216),
+        Color::Indexed(150),
+    ],
+};
+
+pub const LIGHT: Theme = Theme {
+    zebra_stripe: Color::Indexed(254),
+    hover: Color::Indexed(251),
+    warning: Color::Indexed(130),
+    error: Color::Red,
+    binary: Color::Green,
+    reference_diff: Color::Magenta,
+    changed_on_reload: Color::Blue,
+    expected_match: Color::Indexed(28),
+    expected_mismatch: Color::Red,
+    filter_match: Color::Indexed(228),
+    family_palette: [
+        Color::Indexed(24),
+        Color::Indexed(28),
+        Color::Indexed(94),
+        Color::Indexed(88),
+        Color::Indexed(90),
+        Color::Indexed(30),
+        Color::Indexed(130),
+        Color::Indexed(58),
+    ],
+};
+
+/// How long to wait for terminal to answer background color query
+pub const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Parses OSC 11 answer like `\x1b]11;rgb:ffff/ffff/ffff\x07` and tells if background is light
+fn is_light_background(response: &[u8]) -> Option<bool> {
+    let response = std::str::from_utf8(response).ok()?;
+    let rgb = response.split_once("rgb:")?.1;
+    let rgb = rgb.trim_end_matches(['\x07', '\\', '\x1b']);
+    let mut channels = rgb.split('/').map(|hex| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().clamp(1, 4))) - 1;
+        Some(value as f32 / max as f32)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5)
+}
+
+/// Asks terminal for its background color (OSC 11), `None` if it didn't answer in time.
+/// Has to be called before terminal is set up, as it toggles raw mode itself
+#[cfg(unix)]
+pub fn query_light_background(timeout: Duration) -> Option<bool> {
+    use std::{
+        io::{IsTerminal, Write},
+        os::fd::AsRawFd,
+        time::Instant,
+    };
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let query = || {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x1b]11;?\x1b\\").ok()?;
+        stdout.flush().ok()?;
+
+        // Reading byte by byte straight from fd, so that nothing past the answer is consumed
+        let fd = std::io::stdin().as_raw_fd();
+        let deadline = Instant::now() + timeout;
+        let mut response = Vec::new();
+        while !(response.ends_with(b"\x07") || response.ends_with(b"\x1b\\")) {
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: single valid pollfd is passed
+            if unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as i32) } <= 0 {
+                return None;
+            }
+            let mut byte = 0u8;
+            // SAFETY: reading at most one byte into a local
+            if unsafe { libc::read(fd, (&mut byte as *mut u8).cast(), 1) } != 1 {
+                return None;
+            }
+            response.push(byte);
+        }
+        is_light_background(&response)
+    };
+    let res = query();
+    let _ = crossterm::terminal::disable_raw_mode();
+    res
+}
+
+#[cfg(not(unix))]
+pub fn query_light_background(_timeout: Duration) -> Option<bool> {
+    None
+}
+
+#[test]
+fn is_light_background_test() {
+    assert_eq!(
+        is_light_background(b"\x1b]11;rgb:ffff/ffff/ffff\x07"),
+        Some(true)
+    );
+    assert_eq!(
+        is_light_background(b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\"),
+        Some(false)
+    );
+    assert_eq!(is_light_background(b"\x1b]11;rgb:fd/f6/e3\x07"), Some(true));
+    assert_eq!(is_light_background(b"garbage"), None);
+}
+
+#[test]
+fn family_color_test() {
+    assert_eq!(DARK.family_color("XMP"), DARK.family_color("XMP"));
+    let families = [
+        "Exif",
+        "XMP",
+        "IPTC",
+        "MakerNotes",
+        "Composite",
+        "File",
+        "ICC_Profile",
+    ];
+    let colors = families
+        .iter()
+        .map(|f| DARK.family_color(f))
+        .collect::<std::collections::HashSet<_>>();
+    assert!(colors.len() > 1);
+}